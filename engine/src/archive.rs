@@ -0,0 +1,441 @@
+use crate::timestamp::{parse_timestamp, NaiveTimes};
+use crate::witsml_parser::TelemetryData;
+use arrow_array::cast::AsArray;
+use arrow_array::types::{Float64Type, TimestampMillisecondType};
+use arrow_array::{
+    Array, ArrayRef, Float64Array, RecordBatch, StringArray, TimestampMillisecondArray,
+};
+use arrow_schema::{ArrowError, DataType, Field, Schema, SchemaRef, TimeUnit};
+use chrono::{DateTime, NaiveDate, Utc};
+use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+use parquet::arrow::ArrowWriter;
+use parquet::basic::{Compression, ZstdLevel};
+use parquet::errors::ParquetError;
+use parquet::file::properties::WriterProperties;
+use std::collections::BTreeMap;
+use std::fs::{self, File, OpenOptions};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use thiserror::Error;
+
+/// Partition for rows whose timestamp is missing or unparseable.
+const UNDATED_PARTITION: &str = "date=undated";
+
+#[derive(Error, Debug)]
+pub enum ArchiveError {
+    #[error("IO Error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Parquet Error: {0}")]
+    Parquet(#[from] ParquetError),
+    #[error("Arrow Error: {0}")]
+    Arrow(#[from] ArrowError),
+    #[error("Invalid bore id '{0}'")]
+    InvalidBoreId(String),
+}
+
+/// Row filter for [`TelemetryArchive::read`]. Bounds are inclusive and unset
+/// bounds are open; rows without a timestamp only match queries with no time
+/// bounds.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TelemetryQuery {
+    pub from: Option<DateTime<Utc>>,
+    pub to: Option<DateTime<Utc>>,
+    pub min_depth: Option<f64>,
+    pub max_depth: Option<f64>,
+}
+
+impl TelemetryQuery {
+    fn has_time_bounds(&self) -> bool {
+        self.from.is_some() || self.to.is_some()
+    }
+
+    fn matches_day(&self, day: NaiveDate) -> bool {
+        self.from.is_none_or(|f| day >= f.date_naive())
+            && self.to.is_none_or(|t| day <= t.date_naive())
+    }
+
+    fn matches(&self, time: Option<DateTime<Utc>>, depth: f64) -> bool {
+        let time_ok = match time {
+            Some(t) => self.from.is_none_or(|f| t >= f) && self.to.is_none_or(|to| t <= to),
+            None => !self.has_time_bounds(),
+        };
+        time_ok
+            && self.min_depth.is_none_or(|d| depth >= d)
+            && self.max_depth.is_none_or(|d| depth <= d)
+    }
+}
+
+/// Zstd-compressed Parquet store for telemetry, laid out as
+/// `<root>/bore=<id>/date=<YYYY-MM-DD>/part-NNNNN.parquet`.
+///
+/// Each row's timestamp string is stored as written and read back
+/// unchanged. Alongside it, the timestamp is parsed to UTC milliseconds for
+/// partitioning and time queries, with plain date-times placed by the
+/// archive's [`NaiveTimes`] policy (UTC unless set). Rows whose timestamp
+/// can't be parsed go to the `date=undated` partition.
+#[derive(Debug, Clone)]
+pub struct TelemetryArchive {
+    root: PathBuf,
+    naive_times: NaiveTimes,
+}
+
+impl TelemetryArchive {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self {
+            root: root.into(),
+            naive_times: NaiveTimes::default(),
+        }
+    }
+
+    /// Places timestamps without an offset by `naive_times`, e.g. a rig's
+    /// local clock, when writing.
+    pub fn with_naive_times(mut self, naive_times: NaiveTimes) -> Self {
+        self.naive_times = naive_times;
+        self
+    }
+
+    /// Bores with archived telemetry, in name order.
+    pub fn bores(&self) -> Result<Vec<String>, ArchiveError> {
+        if !self.root.exists() {
+            return Ok(Vec::new());
+        }
+        Ok(sorted_entries(&self.root)?
+            .iter()
+            .filter_map(|p| partition_value(p, "bore"))
+            .collect())
+    }
+
+    /// Appends rows for a bore, writing one new part file per UTC day.
+    /// Returns the files written.
+    pub fn write(
+        &self,
+        bore_id: &str,
+        rows: &[TelemetryData],
+    ) -> Result<Vec<PathBuf>, ArchiveError> {
+        let bore_dir = self.bore_dir(bore_id)?;
+        let mut days: BTreeMap<String, Vec<&TelemetryData>> = BTreeMap::new();
+        for row in rows {
+            let partition = match parse_timestamp(&row.timestamp, self.naive_times) {
+                Some(t) => format!("date={}", t.format("%Y-%m-%d")),
+                None => UNDATED_PARTITION.to_string(),
+            };
+            days.entry(partition).or_default().push(row);
+        }
+
+        let props = WriterProperties::builder()
+            .set_compression(Compression::ZSTD(ZstdLevel::default()))
+            .build();
+        let mut written = Vec::new();
+        for (partition, rows) in days {
+            let dir = bore_dir.join(partition);
+            fs::create_dir_all(&dir)?;
+            let (path, file) = create_part(&dir)?;
+            let mut writer = ArrowWriter::try_new(file, schema(), Some(props.clone()))?;
+            writer.write(&to_batch(&rows, self.naive_times)?)?;
+            writer.close()?;
+            written.push(path);
+        }
+        Ok(written)
+    }
+
+    /// Reads a bore's rows matching `query`, in partition and write order.
+    ///
+    /// Day partitions outside the time bounds are skipped without opening
+    /// their files.
+    pub fn read(
+        &self,
+        bore_id: &str,
+        query: &TelemetryQuery,
+    ) -> Result<Vec<TelemetryData>, ArchiveError> {
+        let bore_dir = self.bore_dir(bore_id)?;
+        if !bore_dir.exists() {
+            return Ok(Vec::new());
+        }
+        let mut rows = Vec::new();
+        for day_dir in sorted_entries(&bore_dir)? {
+            let wanted = match partition_value(&day_dir, "date") {
+                Some(day) => match NaiveDate::parse_from_str(&day, "%Y-%m-%d") {
+                    Ok(day) => query.matches_day(day),
+                    Err(_) => !query.has_time_bounds(),
+                },
+                None => false,
+            };
+            if !wanted {
+                continue;
+            }
+            for file in sorted_entries(&day_dir)?
+                .into_iter()
+                .filter(|p| part_number(p).is_some())
+            {
+                let reader =
+                    ParquetRecordBatchReaderBuilder::try_new(File::open(file)?)?.build()?;
+                for batch in reader {
+                    read_batch(&batch?, query, &mut rows);
+                }
+            }
+        }
+        Ok(rows)
+    }
+
+    fn bore_dir(&self, bore_id: &str) -> Result<PathBuf, ArchiveError> {
+        let valid = !bore_id.is_empty()
+            && bore_id
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+            && bore_id != "."
+            && bore_id != "..";
+        if !valid {
+            return Err(ArchiveError::InvalidBoreId(bore_id.to_string()));
+        }
+        Ok(self.root.join(format!("bore={}", bore_id)))
+    }
+}
+
+fn schema() -> SchemaRef {
+    let value = |name| Field::new(name, DataType::Float64, true);
+    Arc::new(Schema::new(vec![
+        Field::new(
+            "timestamp",
+            DataType::Timestamp(TimeUnit::Millisecond, Some("UTC".into())),
+            true,
+        ),
+        Field::new("timestamp_raw", DataType::Utf8, false),
+        Field::new("depth", DataType::Float64, false),
+        value("torque"),
+        value("thrust"),
+        value("mud_flow"),
+        value("bit_rpm"),
+    ]))
+}
+
+fn to_batch(rows: &[&TelemetryData], naive_times: NaiveTimes) -> Result<RecordBatch, ArchiveError> {
+    let times: TimestampMillisecondArray = rows
+        .iter()
+        .map(|r| parse_timestamp(&r.timestamp, naive_times).map(|t| t.timestamp_millis()))
+        .collect();
+    let column = |value: fn(&TelemetryData) -> Option<f64>| -> ArrayRef {
+        Arc::new(rows.iter().map(|r| value(r)).collect::<Float64Array>())
+    };
+    let columns: Vec<ArrayRef> = vec![
+        Arc::new(times.with_timezone("UTC")),
+        Arc::new(StringArray::from_iter_values(
+            rows.iter().map(|r| r.timestamp.as_str()),
+        )),
+        Arc::new(Float64Array::from_iter_values(rows.iter().map(|r| r.depth))),
+        column(|r| r.torque),
+        column(|r| r.thrust),
+        column(|r| r.mud_flow),
+        column(|r| r.bit_rpm),
+    ];
+    Ok(RecordBatch::try_new(schema(), columns)?)
+}
+
+fn read_batch(batch: &RecordBatch, query: &TelemetryQuery, out: &mut Vec<TelemetryData>) {
+    let times = batch.column(0).as_primitive::<TimestampMillisecondType>();
+    let raw = batch.column(1).as_string::<i32>();
+    let depths = batch.column(2).as_primitive::<Float64Type>();
+    let value = |column: usize, i: usize| {
+        let array = batch.column(column).as_primitive::<Float64Type>();
+        (!array.is_null(i)).then(|| array.value(i))
+    };
+    for i in 0..batch.num_rows() {
+        let time = (!times.is_null(i))
+            .then(|| DateTime::from_timestamp_millis(times.value(i)))
+            .flatten();
+        let depth = depths.value(i);
+        if !query.matches(time, depth) {
+            continue;
+        }
+        out.push(TelemetryData {
+            timestamp: raw.value(i).to_string(),
+            depth,
+            torque: value(3, i),
+            thrust: value(4, i),
+            mud_flow: value(5, i),
+            bit_rpm: value(6, i),
+        });
+    }
+}
+
+/// `NNNNN` from a `part-NNNNN.parquet` file name.
+fn part_number(path: &Path) -> Option<u32> {
+    path.file_name()?
+        .to_str()?
+        .strip_prefix("part-")?
+        .strip_suffix(".parquet")?
+        .parse()
+        .ok()
+}
+
+/// Creates the next part file after the highest existing one. Creation is
+/// exclusive, so a concurrent writer that claimed the same number makes
+/// this one move on rather than overwrite it.
+fn create_part(dir: &Path) -> Result<(PathBuf, File), ArchiveError> {
+    let mut next = sorted_entries(dir)?
+        .iter()
+        .filter_map(|p| part_number(p))
+        .max()
+        .map_or(0, |n| n + 1);
+    loop {
+        let path = dir.join(format!("part-{:05}.parquet", next));
+        match OpenOptions::new().write(true).create_new(true).open(&path) {
+            Ok(file) => return Ok((path, file)),
+            Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => next += 1,
+            Err(e) => return Err(e.into()),
+        }
+    }
+}
+
+fn sorted_entries(dir: &Path) -> Result<Vec<PathBuf>, ArchiveError> {
+    let mut entries = fs::read_dir(dir)?
+        .map(|e| e.map(|e| e.path()))
+        .collect::<Result<Vec<_>, _>>()?;
+    entries.sort();
+    Ok(entries)
+}
+
+/// `value` from a `key=value` directory name.
+fn partition_value(path: &Path, key: &str) -> Option<String> {
+    path.file_name()?
+        .to_str()?
+        .strip_prefix(key)?
+        .strip_prefix('=')
+        .map(str::to_string)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn row(timestamp: &str, depth: f64) -> TelemetryData {
+        TelemetryData {
+            timestamp: timestamp.to_string(),
+            depth,
+            torque: Some(1500.0 + depth),
+            thrust: None,
+            mud_flow: Some(40.0),
+            bit_rpm: Some(60.0),
+        }
+    }
+
+    fn scratch_dir(name: &str) -> PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("engine-archive-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        dir
+    }
+
+    #[test]
+    fn test_round_trip_partitioned() {
+        let dir = scratch_dir("round-trip");
+        let archive = TelemetryArchive::new(&dir);
+        let rows = vec![
+            row("2024-05-01T23:59:59Z", 100.0),
+            row("2024-05-02T00:00:01.500Z", 101.0),
+            row("", 102.0),
+        ];
+        let files = archive.write("bore-1", &rows).unwrap();
+        assert_eq!(files.len(), 3);
+        assert!(dir
+            .join("bore=bore-1/date=2024-05-02/part-00000.parquet")
+            .exists());
+        // Stray files don't shift the numbering; gaps continue from the top.
+        let day = dir.join("bore=bore-1/date=2024-05-01");
+        fs::write(day.join("notes.txt"), "not a part").unwrap();
+        fs::rename(
+            day.join("part-00000.parquet"),
+            day.join("part-00007.parquet"),
+        )
+        .unwrap();
+        archive.write("bore-1", &rows[..1]).unwrap();
+        assert!(day.join("part-00008.parquet").exists());
+
+        let all = archive.read("bore-1", &TelemetryQuery::default()).unwrap();
+        assert_eq!(
+            all,
+            vec![
+                rows[0].clone(),
+                rows[0].clone(),
+                rows[1].clone(),
+                rows[2].clone()
+            ]
+        );
+        assert_eq!(archive.bores().unwrap(), vec!["bore-1"]);
+        assert!(matches!(
+            archive.write("../etc", &rows),
+            Err(ArchiveError::InvalidBoreId(_))
+        ));
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_round_trip_plain_timestamps() {
+        let dir = scratch_dir("plain-time");
+        let archive = TelemetryArchive::new(&dir);
+        let rows = vec![
+            row("2024-05-01 10:00:00", 100.0),
+            row("2024-05-01T10:00:01", 101.0),
+            row("shift start", 102.0),
+        ];
+        archive.write("bore-3", &rows).unwrap();
+        assert!(dir.join("bore=bore-3/date=2024-05-01").exists());
+        assert!(dir.join("bore=bore-3/date=undated").exists());
+        assert_eq!(
+            archive.read("bore-3", &TelemetryQuery::default()).unwrap(),
+            rows
+        );
+
+        let morning = TelemetryQuery {
+            from: Some("2024-05-01T10:00:00Z".parse().unwrap()),
+            to: Some("2024-05-01T10:00:00.999Z".parse().unwrap()),
+            ..Default::default()
+        };
+        assert_eq!(archive.read("bore-3", &morning).unwrap(), rows[..1]);
+
+        // Late evening on a UTC-5 rig clock is the next UTC day.
+        let rig = TelemetryArchive::new(&dir).with_naive_times(NaiveTimes::Offset(
+            chrono::FixedOffset::west_opt(5 * 3600).unwrap(),
+        ));
+        rig.write("bore-4", &[row("2024-05-01 22:30:00", 10.0)])
+            .unwrap();
+        assert!(dir.join("bore=bore-4/date=2024-05-02").exists());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_time_and_depth_queries() {
+        let dir = scratch_dir("query");
+        let archive = TelemetryArchive::new(&dir);
+        let rows: Vec<_> = (0..48)
+            .map(|h| {
+                row(
+                    &format!("2024-05-{:02}T{:02}:00:00Z", 1 + h / 24, h % 24),
+                    h as f64 * 10.0,
+                )
+            })
+            .collect();
+        archive.write("bore-2", &rows).unwrap();
+
+        let by_time = TelemetryQuery {
+            from: Some("2024-05-01T22:00:00Z".parse().unwrap()),
+            to: Some("2024-05-02T01:00:00Z".parse().unwrap()),
+            ..Default::default()
+        };
+        let depths: Vec<f64> = archive
+            .read("bore-2", &by_time)
+            .unwrap()
+            .iter()
+            .map(|r| r.depth)
+            .collect();
+        assert_eq!(depths, vec![220.0, 230.0, 240.0, 250.0]);
+
+        let by_depth = TelemetryQuery {
+            min_depth: Some(95.0),
+            max_depth: Some(120.0),
+            ..Default::default()
+        };
+        assert_eq!(archive.read("bore-2", &by_depth).unwrap().len(), 3);
+        assert!(archive.read("missing", &by_depth).unwrap().is_empty());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,336 @@
+use crate::interpolate::{PathStation, SurveyPath};
+use crate::uncertainty::StationCovariance;
+use crate::Coordinate3D;
+use serde::Serialize;
+
+/// Path sampling interval (ft) for the coarse pass against line obstacles.
+const SCAN_STEP_FT: f64 = 1.0;
+/// Offsets smaller than this (ft) count as level or inline.
+const SIDE_TOLERANCE_FT: f64 = 1e-3;
+
+/// A mapped utility or structure, mirroring the `Obstacle` table.
+///
+/// Coordinates share the bore path's frame: north/east from the entry and
+/// TVD below it, in ft.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Obstacle {
+    pub name: String,
+    pub start: Coordinate3D,
+    /// Other end of a line obstacle; `None` for a point obstacle.
+    pub end: Option<Coordinate3D>,
+    /// Outside diameter in inches.
+    pub diameter: Option<f64>,
+    /// Required wall-to-wall clearance in ft.
+    pub safety_buffer: f64,
+}
+
+/// Where the obstacle sits relative to the bore at closest approach.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum VerticalSide {
+    Above,
+    Below,
+    Level,
+}
+
+/// Side of the bore, looking downhole, the obstacle lies on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LateralSide {
+    Left,
+    Right,
+    Inline,
+}
+
+/// Positional uncertainty to fold into the clearance figures.
+#[derive(Debug, Clone, Copy)]
+pub struct UncertaintyBand<'a> {
+    pub stations: &'a [StationCovariance],
+    pub confidence: f64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ObstacleClearance {
+    pub name: String,
+    /// MD of closest approach.
+    pub measured_depth: f64,
+    /// Centreline-to-centreline distance in ft.
+    pub separation: f64,
+    /// Wall-to-wall distance in ft: separation less both radii.
+    pub clearance: f64,
+    pub vertical: VerticalSide,
+    pub lateral: LateralSide,
+    /// Clearance after moving the bore towards the obstacle by its
+    /// uncertainty at the requested confidence.
+    pub clearance_at_confidence: Option<f64>,
+    /// `clearance` (or `clearance_at_confidence` when given) is inside the
+    /// obstacle's safety buffer.
+    pub buffer_violation: bool,
+}
+
+fn distance(a: &Coordinate3D, b: &Coordinate3D) -> f64 {
+    ((a.north - b.north).powi(2) + (a.east - b.east).powi(2) + (a.tvd - b.tvd).powi(2)).sqrt()
+}
+
+fn position(station: &PathStation) -> Coordinate3D {
+    Coordinate3D {
+        north: station.north,
+        east: station.east,
+        tvd: station.tvd,
+    }
+}
+
+/// Closest point to `p` on the obstacle's axis.
+fn closest_on_obstacle(obstacle: &Obstacle, p: &Coordinate3D) -> Coordinate3D {
+    let a = &obstacle.start;
+    let Some(b) = &obstacle.end else {
+        return a.clone();
+    };
+    let ab = [b.north - a.north, b.east - a.east, b.tvd - a.tvd];
+    let ap = [p.north - a.north, p.east - a.east, p.tvd - a.tvd];
+    let length2: f64 = ab.iter().map(|x| x * x).sum();
+    let t = if length2 == 0.0 {
+        0.0
+    } else {
+        ((0..3).map(|k| ab[k] * ap[k]).sum::<f64>() / length2).clamp(0.0, 1.0)
+    };
+    Coordinate3D {
+        north: a.north + t * ab[0],
+        east: a.east + t * ab[1],
+        tvd: a.tvd + t * ab[2],
+    }
+}
+
+/// MD along `path` closest to a line obstacle: a 1 ft scan followed by a
+/// golden-section search around the best sample.
+fn closest_md_to_line(path: &SurveyPath, obstacle: &Obstacle) -> f64 {
+    let gap = |md: f64| {
+        path.at(md).map_or(f64::INFINITY, |s| {
+            let p = position(&s);
+            distance(&p, &closest_on_obstacle(obstacle, &p))
+        })
+    };
+    let best = path
+        .every(SCAN_STEP_FT)
+        .iter()
+        .map(|s| (s.measured_depth, gap(s.measured_depth)))
+        .min_by(|a, b| a.1.total_cmp(&b.1))
+        .map_or(path.start_md(), |(md, _)| md);
+
+    let (mut lo, mut hi) = (
+        (best - SCAN_STEP_FT).max(path.start_md()),
+        (best + SCAN_STEP_FT).min(path.end_md()),
+    );
+    let ratio = (5f64.sqrt() - 1.0) / 2.0;
+    for _ in 0..60 {
+        let a = hi - ratio * (hi - lo);
+        let b = lo + ratio * (hi - lo);
+        if gap(a) < gap(b) {
+            hi = b;
+        } else {
+            lo = a;
+        }
+    }
+    (lo + hi) / 2.0
+}
+
+/// Covariance at `md`, linearly interpolated between stations.
+fn covariance_at(stations: &[StationCovariance], md: f64) -> Option<StationCovariance> {
+    let after = stations.partition_point(|s| s.measured_depth < md);
+    let upper = stations.get(after)?;
+    let Some(lower) = after.checked_sub(1).map(|i| &stations[i]) else {
+        return Some(upper.clone());
+    };
+    let span = upper.measured_depth - lower.measured_depth;
+    let f = if span > 0.0 {
+        (md - lower.measured_depth) / span
+    } else {
+        1.0
+    };
+    let mut covariance = lower.covariance;
+    for (i, row) in covariance.iter_mut().enumerate() {
+        for (j, cell) in row.iter_mut().enumerate() {
+            *cell += f * (upper.covariance[i][j] - *cell);
+        }
+    }
+    Some(StationCovariance {
+        measured_depth: md,
+        tvd: lower.tvd + f * (upper.tvd - lower.tvd),
+        covariance,
+    })
+}
+
+/// Closest approach of the bore to each obstacle.
+///
+/// `bore_diameter_in` is the reamed hole (or product) diameter used for the
+/// wall-to-wall clearance. Point obstacles are treated as spheres of their
+/// diameter, line obstacles as cylinders along their axis.
+pub fn analyze_clearance(
+    path: &SurveyPath,
+    obstacles: &[Obstacle],
+    bore_diameter_in: f64,
+    uncertainty: Option<UncertaintyBand>,
+) -> Vec<ObstacleClearance> {
+    obstacles
+        .iter()
+        .map(|obstacle| {
+            let station = match obstacle.end {
+                None => path.nearest_md(&obstacle.start).station,
+                Some(_) => {
+                    let md = closest_md_to_line(path, obstacle);
+                    path.at(md)
+                        .unwrap_or_else(|| path.nearest_md(&obstacle.start).station)
+                }
+            };
+            let bore = position(&station);
+            let target = closest_on_obstacle(obstacle, &bore);
+            let separation = distance(&bore, &target);
+            let radii = (obstacle.diameter.unwrap_or(0.0) + bore_diameter_in) / 24.0;
+            let clearance = separation - radii;
+
+            let offset = [
+                target.north - bore.north,
+                target.east - bore.east,
+                target.tvd - bore.tvd,
+            ];
+            let vertical = if offset[2] < -SIDE_TOLERANCE_FT {
+                VerticalSide::Above
+            } else if offset[2] > SIDE_TOLERANCE_FT {
+                VerticalSide::Below
+            } else {
+                VerticalSide::Level
+            };
+            let az = station.azimuth.to_radians();
+            let right = -offset[0] * az.sin() + offset[1] * az.cos();
+            let lateral = if right > SIDE_TOLERANCE_FT {
+                LateralSide::Right
+            } else if right < -SIDE_TOLERANCE_FT {
+                LateralSide::Left
+            } else {
+                LateralSide::Inline
+            };
+
+            let clearance_at_confidence = uncertainty.and_then(|band| {
+                let covariance = covariance_at(band.stations, station.measured_depth)?;
+                // On the centreline any direction could close the gap, so
+                // take the worst horizontal or vertical axis.
+                let half_width = covariance
+                    .half_width_along(offset, band.confidence)
+                    .or_else(|| {
+                        let ellipse = covariance.ellipse(band.confidence)?;
+                        Some(ellipse.semi_major.max(ellipse.vertical))
+                    })?;
+                Some(clearance - half_width)
+            });
+
+            ObstacleClearance {
+                name: obstacle.name.clone(),
+                measured_depth: station.measured_depth,
+                separation,
+                clearance,
+                vertical,
+                lateral,
+                buffer_violation: clearance_at_confidence.unwrap_or(clearance)
+                    < obstacle.safety_buffer,
+                clearance_at_confidence,
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::uncertainty::{propagate_uncertainty, ErrorModel};
+    use crate::SurveyPoint;
+
+    fn station(md: f64, pitch: f64) -> SurveyPoint {
+        SurveyPoint {
+            measured_depth: md,
+            inclination: pitch,
+            azimuth: 0.0,
+        }
+    }
+
+    /// Due north, levelling out at 20 ft TVD from 200 ft MD onward.
+    fn bore() -> Vec<SurveyPoint> {
+        vec![
+            station(0.0, 12.0),
+            station(100.0, 10.0),
+            station(200.0, 0.0),
+            station(500.0, 0.0),
+        ]
+    }
+
+    fn point(north: f64, east: f64, tvd: f64) -> Coordinate3D {
+        Coordinate3D { north, east, tvd }
+    }
+
+    #[test]
+    fn test_line_crossing_above() {
+        let surveys = bore();
+        let path = SurveyPath::new(&surveys).unwrap();
+        let at = path.at(350.0).unwrap();
+        // A 12" gas main running east-west 8 ft above the bore.
+        let gas = Obstacle {
+            name: "gas".into(),
+            start: point(at.north, -100.0, at.tvd - 8.0),
+            end: Some(point(at.north, 100.0, at.tvd - 8.0)),
+            diameter: Some(12.0),
+            safety_buffer: 5.0,
+        };
+        let result = analyze_clearance(&path, std::slice::from_ref(&gas), 12.0, None);
+        assert!((result[0].measured_depth - 350.0).abs() < 1e-3);
+        assert!((result[0].separation - 8.0).abs() < 1e-6);
+        assert!((result[0].clearance - 7.0).abs() < 1e-6);
+        assert_eq!(result[0].vertical, VerticalSide::Above);
+        assert_eq!(result[0].lateral, LateralSide::Inline);
+        assert!(!result[0].buffer_violation);
+        assert_eq!(result[0].clearance_at_confidence, None);
+
+        // Uncertainty eats into the 7 ft and breaks the 5 ft buffer.
+        let model = ErrorModel {
+            locator_depth_fraction: None,
+            pitch_bias_deg: 0.3,
+            ..Default::default()
+        };
+        let covariances = propagate_uncertainty(&surveys, &model).unwrap();
+        let band = UncertaintyBand {
+            stations: &covariances,
+            confidence: 0.95,
+        };
+        let result = analyze_clearance(&path, &[gas], 12.0, Some(band));
+        let uncertain = result[0].clearance_at_confidence.unwrap();
+        assert!(uncertain < 5.0 && uncertain > 0.0);
+        assert!(result[0].buffer_violation);
+    }
+
+    #[test]
+    fn test_point_obstacle_side() {
+        let path = SurveyPath::new(&bore()).unwrap();
+        let at = path.at(300.0).unwrap();
+        let manhole = Obstacle {
+            name: "manhole".into(),
+            start: point(at.north, -3.0, at.tvd + 4.0),
+            end: None,
+            diameter: None,
+            safety_buffer: 2.0,
+        };
+        let result = analyze_clearance(&path, &[manhole], 0.0, None);
+        assert!((result[0].measured_depth - 300.0).abs() < 1e-3);
+        assert!((result[0].separation - 5.0).abs() < 1e-6);
+        assert_eq!(result[0].vertical, VerticalSide::Below);
+        assert_eq!(result[0].lateral, LateralSide::Left);
+        assert!(!result[0].buffer_violation);
+    }
+
+    #[test]
+    fn test_covariance_interpolation() {
+        let covariances = propagate_uncertainty(&bore(), &ErrorModel::default()).unwrap();
+        let mid = covariance_at(&covariances, 350.0).unwrap();
+        let expected = (covariances[2].covariance[1][1] + covariances[3].covariance[1][1]) / 2.0;
+        assert!((mid.covariance[1][1] - expected).abs() < 1e-12);
+        assert!(covariance_at(&covariances, 600.0).is_none());
+    }
+}
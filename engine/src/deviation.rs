@@ -0,0 +1,290 @@
+use crate::interpolate::SurveyPath;
+use crate::{calculate_path, SurveyError, SurveyPoint};
+use serde::{Deserialize, Serialize};
+
+/// Slack (ft) in deciding that a station lies past an end of the plan.
+const PLAN_END_SLACK: f64 = 1e-3;
+
+/// Allowed offset from the plan, as an elliptical tube around it (ft).
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ToleranceTube {
+    pub high_low: f64,
+    pub left_right: f64,
+}
+
+impl Default for ToleranceTube {
+    fn default() -> Self {
+        Self {
+            high_low: 2.0,
+            left_right: 2.0,
+        }
+    }
+}
+
+impl ToleranceTube {
+    fn contains(&self, high: f64, right: f64) -> bool {
+        let scaled = |offset: f64, limit: f64| {
+            if limit > 0.0 {
+                offset / limit
+            } else if offset == 0.0 {
+                0.0
+            } else {
+                f64::INFINITY
+            }
+        };
+        scaled(high, self.high_low).hypot(scaled(right, self.left_right)) <= 1.0
+    }
+}
+
+/// How one actual station sits against the plan.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StationDeviation {
+    pub measured_depth: f64,
+    /// Planned MD closest to the actual station.
+    pub planned_md: f64,
+    /// Offset above the plan in ft; negative is low.
+    pub high: f64,
+    /// Offset to the right of the plan looking downhole in ft; negative is left.
+    pub right: f64,
+    /// Along-hole distance past the end of the plan in ft, negative before
+    /// its start; zero when the station lies alongside the plan.
+    pub beyond_plan: f64,
+    /// Straight-line distance to the plan in ft.
+    pub distance: f64,
+    /// Actual less planned pitch in degrees.
+    pub pitch_difference: f64,
+    /// Actual less planned azimuth in degrees, within -180..180.
+    pub azimuth_difference: f64,
+    pub within_tolerance: bool,
+}
+
+/// A run of consecutive stations outside the tolerance tube.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ToleranceExcursion {
+    pub start_md: f64,
+    pub end_md: f64,
+    pub max_distance: f64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DeviationReport {
+    pub stations: Vec<StationDeviation>,
+    pub max_distance: f64,
+    pub max_distance_md: f64,
+    pub mean_distance: f64,
+    pub rms_distance: f64,
+    pub max_high_low: f64,
+    pub max_left_right: f64,
+    pub excursions: Vec<ToleranceExcursion>,
+}
+
+/// Groups consecutive out-of-tolerance stations into runs.
+fn excursions(stations: &[StationDeviation]) -> Vec<ToleranceExcursion> {
+    let mut excursions: Vec<ToleranceExcursion> = Vec::new();
+    let mut open = false;
+    for s in stations {
+        if s.within_tolerance {
+            open = false;
+            continue;
+        }
+        match excursions.last_mut() {
+            Some(run) if open => {
+                run.end_md = s.measured_depth;
+                run.max_distance = run.max_distance.max(s.distance);
+            }
+            _ => excursions.push(ToleranceExcursion {
+                start_md: s.measured_depth,
+                end_md: s.measured_depth,
+                max_distance: s.distance,
+            }),
+        }
+        open = true;
+    }
+    excursions
+}
+
+/// Compares the surveyed bore against its plan, station by station.
+///
+/// Both are surveys in the same north reference; the plan is followed along
+/// its minimum-curvature arcs, so its stations needn't line up with the
+/// actual ones. High/low and left/right are measured in the plane normal to
+/// the plan at the nearest point. Stations past either end of the plan are
+/// outside tolerance however close they are, with the overshoot reported in
+/// `beyond_plan`.
+pub fn deviation_report(
+    actual: &[SurveyPoint],
+    planned: &[SurveyPoint],
+    tolerance: &ToleranceTube,
+) -> Result<DeviationReport, SurveyError> {
+    let plan = SurveyPath::new(planned)?;
+    let coords = calculate_path(actual)?;
+
+    let stations: Vec<StationDeviation> = actual
+        .iter()
+        .zip(&coords)
+        .map(|(survey, position)| {
+            let nearest = plan.nearest_md(position).station;
+            let offset = [
+                position.north - nearest.north,
+                position.east - nearest.east,
+                position.tvd - nearest.tvd,
+            ];
+            let (pitch, az) = (nearest.pitch.to_radians(), nearest.azimuth.to_radians());
+            let up = [pitch.sin() * az.cos(), pitch.sin() * az.sin(), -pitch.cos()];
+            let high: f64 = (0..3).map(|k| offset[k] * up[k]).sum();
+            let right = -offset[0] * az.sin() + offset[1] * az.cos();
+            let ahead = [pitch.cos() * az.cos(), pitch.cos() * az.sin(), pitch.sin()];
+            let along: f64 = (0..3).map(|k| offset[k] * ahead[k]).sum();
+            let beyond_plan = if (along < -PLAN_END_SLACK
+                && nearest.measured_depth <= plan.start_md() + PLAN_END_SLACK)
+                || (along > PLAN_END_SLACK
+                    && nearest.measured_depth >= plan.end_md() - PLAN_END_SLACK)
+            {
+                along
+            } else {
+                0.0
+            };
+            let azimuth_difference =
+                (survey.azimuth - nearest.azimuth + 180.0).rem_euclid(360.0) - 180.0;
+            StationDeviation {
+                measured_depth: survey.measured_depth,
+                planned_md: nearest.measured_depth,
+                high,
+                right,
+                beyond_plan,
+                distance: offset.iter().map(|d| d * d).sum::<f64>().sqrt(),
+                pitch_difference: survey.inclination - nearest.pitch,
+                azimuth_difference,
+                within_tolerance: beyond_plan == 0.0 && tolerance.contains(high, right),
+            }
+        })
+        .collect();
+
+    let excursions = excursions(&stations);
+    let count = stations.len() as f64;
+    let worst = stations
+        .iter()
+        .max_by(|a, b| a.distance.total_cmp(&b.distance))
+        .map_or((0.0, 0.0), |s| (s.distance, s.measured_depth));
+    Ok(DeviationReport {
+        max_distance: worst.0,
+        max_distance_md: worst.1,
+        mean_distance: stations.iter().map(|s| s.distance).sum::<f64>() / count,
+        rms_distance: (stations.iter().map(|s| s.distance.powi(2)).sum::<f64>() / count).sqrt(),
+        max_high_low: stations.iter().map(|s| s.high.abs()).fold(0.0, f64::max),
+        max_left_right: stations.iter().map(|s| s.right.abs()).fold(0.0, f64::max),
+        excursions,
+        stations,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn station(md: f64, pitch: f64, azimuth: f64) -> SurveyPoint {
+        SurveyPoint {
+            measured_depth: md,
+            inclination: pitch,
+            azimuth,
+        }
+    }
+
+    fn plan() -> Vec<SurveyPoint> {
+        vec![station(0.0, 0.0, 90.0), station(300.0, 0.0, 90.0)]
+    }
+
+    #[test]
+    fn test_high_and_right_of_plan() {
+        // Drilling due east but rising and drifting south of the plan.
+        let actual = vec![
+            station(0.0, 0.0, 90.0),
+            station(100.0, -1.0, 91.0),
+            station(200.0, -1.0, 91.0),
+        ];
+        let report = deviation_report(&actual, &plan(), &ToleranceTube::default()).unwrap();
+        let last = &report.stations[2];
+        assert!(last.high > 1.0);
+        assert!(last.right > 1.0);
+        assert!((last.distance - last.high.hypot(last.right)).abs() < 1e-6);
+        assert!((last.pitch_difference + 1.0).abs() < 1e-9);
+        assert!((last.azimuth_difference - 1.0).abs() < 1e-9);
+        assert!((last.planned_md - 200.0).abs() < 0.1);
+        assert_eq!(report.max_distance_md, 200.0);
+        assert!(report.stations[0].within_tolerance);
+        assert!(!last.within_tolerance);
+        assert_eq!(report.excursions.len(), 1);
+        assert_eq!(report.excursions[0].end_md, 200.0);
+    }
+
+    #[test]
+    fn test_station_past_end_of_plan() {
+        // On line, but drilled 100 ft further than planned.
+        let actual: Vec<SurveyPoint> = (0..=4)
+            .map(|i| station(i as f64 * 100.0, 0.0, 90.0))
+            .collect();
+        let report = deviation_report(&actual, &plan(), &ToleranceTube::default()).unwrap();
+        let last = &report.stations[4];
+        assert!((last.planned_md - 300.0).abs() < 0.1);
+        assert!(last.high.abs() < 1e-6 && last.right.abs() < 1e-6);
+        assert!((last.beyond_plan - 100.0).abs() < 0.1);
+        assert!(!last.within_tolerance);
+        assert!(report.stations[..4]
+            .iter()
+            .all(|s| s.within_tolerance && s.beyond_plan == 0.0));
+        assert_eq!(report.excursions.len(), 1);
+        assert_eq!(report.excursions[0].start_md, 400.0);
+    }
+
+    #[test]
+    fn test_azimuth_wraps_and_tube_shape() {
+        let plan = vec![station(0.0, 0.0, 359.0), station(500.0, 0.0, 359.0)];
+        let actual = vec![station(0.0, 0.0, 1.0), station(50.0, 0.0, 1.0)];
+        let report = deviation_report(&actual, &plan, &ToleranceTube::default()).unwrap();
+        assert!((report.stations[1].azimuth_difference - 2.0).abs() < 1e-9);
+
+        let tube = ToleranceTube {
+            high_low: 1.0,
+            left_right: 3.0,
+        };
+        assert!(tube.contains(0.5, 2.0));
+        assert!(!tube.contains(1.0, 2.0));
+        let no_vertical = ToleranceTube {
+            high_low: 0.0,
+            left_right: 1.0,
+        };
+        assert!(!no_vertical.contains(0.1, 0.0));
+    }
+
+    #[test]
+    fn test_excursion_runs() {
+        let flagged = |md: f64, distance: f64, within_tolerance: bool| StationDeviation {
+            measured_depth: md,
+            planned_md: md,
+            high: distance,
+            right: 0.0,
+            beyond_plan: 0.0,
+            distance,
+            pitch_difference: 0.0,
+            azimuth_difference: 0.0,
+            within_tolerance,
+        };
+        let stations = [
+            flagged(0.0, 0.0, true),
+            flagged(10.0, 3.0, false),
+            flagged(20.0, 4.0, false),
+            flagged(30.0, 1.0, true),
+            flagged(40.0, 2.5, false),
+        ];
+        let runs = excursions(&stations);
+        assert_eq!(runs.len(), 2);
+        assert_eq!((runs[0].start_md, runs[0].end_md), (10.0, 20.0));
+        assert_eq!(runs[0].max_distance, 4.0);
+        assert_eq!((runs[1].start_md, runs[1].end_md), (40.0, 40.0));
+        assert!(deviation_report(&[], &plan(), &ToleranceTube::default()).is_err());
+    }
+}
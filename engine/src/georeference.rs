@@ -0,0 +1,155 @@
+use crate::north_reference::{utm15n_scale_factor, AzimuthCorrection, NorthReference};
+use crate::Coordinate3D;
+use geo::Point;
+use mn_compliance_core::{ComplianceError, MnProjector};
+
+const FEET_PER_METRE: f64 = 1.0 / 0.3048;
+/// Mean earth radius (m) for reducing ground distances to the ellipsoid.
+const EARTH_RADIUS_M: f64 = 6_371_000.0;
+
+/// Where the bore leaves the surface.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct EntryPoint {
+    pub latitude: f64,
+    pub longitude: f64,
+    /// Ground elevation at the entry in ft. Also used as the height for the
+    /// elevation factor, treating it as height above the ellipsoid; in
+    /// Minnesota the ~30 m geoid separation this ignores is under 5 ppm.
+    pub ground_elevation: f64,
+}
+
+/// A path point in both the bore's local frame and map coordinates.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GeoreferencedPoint {
+    pub local: Coordinate3D,
+    /// UTM 15N easting in metres.
+    pub easting: f64,
+    /// UTM 15N northing in metres.
+    pub northing: f64,
+    pub latitude: f64,
+    pub longitude: f64,
+    /// Elevation in ft: ground elevation at the entry less TVD.
+    pub elevation: f64,
+}
+
+/// Places a computed path on the map.
+///
+/// `reference` is the north the path's north/east axes follow (e.g. the
+/// `to` reference given to `calculate_path_in`). Offsets are rotated onto
+/// UTM 15N grid north using the declination and the grid convergence at the
+/// entry, scaled from ground to grid distance with the combined (point scale
+/// times elevation) factor at the entry, added to the projected entry point,
+/// then projected back to WGS84. Across a bore the factor varies by well
+/// under a part per million, so one value at the entry is used throughout.
+pub fn georeference_path(
+    path: &[Coordinate3D],
+    entry: &EntryPoint,
+    reference: NorthReference,
+    declination: f64,
+    projector: &MnProjector,
+) -> Result<Vec<GeoreferencedPoint>, ComplianceError> {
+    let correction = AzimuthCorrection::for_utm15n(declination, entry.latitude, entry.longitude);
+    let rotation = correction
+        .convert(0.0, reference, NorthReference::Grid)
+        .to_radians();
+    let origin = projector.project_to_utm(Point::new(entry.longitude, entry.latitude))?;
+    let height = entry.ground_elevation / FEET_PER_METRE;
+    let grid_metres_per_foot = utm15n_scale_factor(entry.latitude, entry.longitude)
+        * EARTH_RADIUS_M
+        / (EARTH_RADIUS_M + height)
+        / FEET_PER_METRE;
+
+    path.iter()
+        .map(|local| {
+            let north = local.north * rotation.cos() - local.east * rotation.sin();
+            let east = local.north * rotation.sin() + local.east * rotation.cos();
+            let easting = origin.x() + east * grid_metres_per_foot;
+            let northing = origin.y() + north * grid_metres_per_foot;
+            let wgs84 = projector.project_to_wgs84(Point::new(easting, northing))?;
+            Ok(GeoreferencedPoint {
+                local: local.clone(),
+                easting,
+                northing,
+                latitude: wgs84.y(),
+                longitude: wgs84.x(),
+                elevation: entry.ground_elevation - local.tvd,
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::north_reference::utm15n_grid_convergence;
+
+    fn entry() -> EntryPoint {
+        EntryPoint {
+            latitude: 44.9778,
+            longitude: -93.265,
+            ground_elevation: 830.0,
+        }
+    }
+
+    fn local(north: f64, east: f64, tvd: f64) -> Coordinate3D {
+        Coordinate3D { north, east, tvd }
+    }
+
+    #[test]
+    fn test_entry_maps_to_itself() {
+        let projector = MnProjector::new().unwrap();
+        let points = georeference_path(
+            &[local(0.0, 0.0, 0.0)],
+            &entry(),
+            NorthReference::True,
+            0.0,
+            &projector,
+        )
+        .unwrap();
+        assert!((points[0].latitude - 44.9778).abs() < 1e-7);
+        assert!((points[0].longitude + 93.265).abs() < 1e-7);
+        assert!(points[0].easting > 470_000.0 && points[0].easting < 490_000.0);
+        assert_eq!(points[0].elevation, 830.0);
+    }
+
+    #[test]
+    fn test_offsets_follow_reference() {
+        let projector = MnProjector::new().unwrap();
+        let path = [local(0.0, 0.0, 0.0), local(1000.0, 0.0, 25.0)];
+
+        // 1000 ft of ground due grid north moves only the northing, by
+        // 304.8 m shrunk by k = 0.999605 near the central meridian and by
+        // the elevation factor for 253 m (0.999960).
+        let grid =
+            georeference_path(&path, &entry(), NorthReference::Grid, 0.0, &projector).unwrap();
+        assert!((grid[1].northing - grid[0].northing - 304.8 * 0.999_566).abs() < 1e-3);
+        assert!((grid[1].easting - grid[0].easting).abs() < 1e-6);
+        assert_eq!(grid[1].elevation, 805.0);
+
+        // Due true north is rotated by the (negative) convergence west of
+        // the central meridian, so it gains easting on the grid.
+        let truth =
+            georeference_path(&path, &entry(), NorthReference::True, 0.0, &projector).unwrap();
+        let gamma = utm15n_grid_convergence(44.9778, -93.265).to_radians();
+        let grid_length = grid[1].northing - grid[0].northing;
+        assert!((truth[1].easting - truth[0].easting + grid_length * gamma.sin()).abs() < 1e-6);
+        assert!((truth[1].longitude - truth[0].longitude).abs() < 1e-6);
+        assert!(truth[1].latitude > truth[0].latitude);
+    }
+
+    #[test]
+    fn test_scale_factor_matches_projection() {
+        use geo::GeodesicDistance;
+        // Grid over ellipsoid distance for a short north-south line near
+        // the zone edge, where k departs most from k0.
+        let projector = MnProjector::new().unwrap();
+        let (a, b) = (Point::new(-90.5, 45.0), Point::new(-90.5, 45.01));
+        let (pa, pb) = (
+            projector.project_to_utm(a).unwrap(),
+            projector.project_to_utm(b).unwrap(),
+        );
+        let grid = (pb.x() - pa.x()).hypot(pb.y() - pa.y());
+        let k = grid / a.geodesic_distance(&b);
+        assert!((k - utm15n_scale_factor(45.005, -90.5)).abs() < 1e-6);
+    }
+}
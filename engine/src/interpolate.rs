@@ -0,0 +1,285 @@
+use crate::{calculate_path, Coordinate3D, SurveyError, SurveyPoint};
+use serde::Serialize;
+
+/// Samples per course for the coarse pass of [`SurveyPath::nearest_md`].
+const NEAREST_SAMPLES: usize = 32;
+
+/// Position and attitude at a measured depth along the bore.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct PathStation {
+    pub measured_depth: f64,
+    pub north: f64,
+    pub east: f64,
+    pub tvd: f64,
+    /// HDD pitch in degrees (0 = horizontal, positive = descending).
+    pub pitch: f64,
+    pub azimuth: f64,
+}
+
+/// Result of [`SurveyPath::nearest_md`].
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct NearestStation {
+    pub station: PathStation,
+    /// Straight-line distance (ft) from the query point to `station`.
+    pub distance: f64,
+}
+
+/// Unit tangent as (north, east, down) from HDD pitch and azimuth.
+fn tangent(pitch: f64, azimuth: f64) -> [f64; 3] {
+    let inc = (90.0 - pitch).to_radians();
+    let az = azimuth.to_radians();
+    [inc.sin() * az.cos(), inc.sin() * az.sin(), inc.cos()]
+}
+
+/// A surveyed bore that can be queried between stations.
+///
+/// Positions follow the minimum-curvature arc of each course, so stations
+/// match `calculate_path` exactly and intermediate points lie on the same
+/// circular arc the method assumes.
+#[derive(Debug, Clone)]
+pub struct SurveyPath {
+    stations: Vec<SurveyPoint>,
+    coords: Vec<Coordinate3D>,
+}
+
+impl SurveyPath {
+    pub fn new(surveys: &[SurveyPoint]) -> Result<Self, SurveyError> {
+        let coords = calculate_path(surveys)?;
+        Ok(Self {
+            stations: surveys.to_vec(),
+            coords,
+        })
+    }
+
+    pub fn start_md(&self) -> f64 {
+        self.stations[0].measured_depth
+    }
+
+    pub fn end_md(&self) -> f64 {
+        self.stations[self.stations.len() - 1].measured_depth
+    }
+
+    /// Position and attitude at `md`, or `None` outside the surveyed range.
+    pub fn at(&self, md: f64) -> Option<PathStation> {
+        if !(self.start_md()..=self.end_md()).contains(&md) {
+            return None;
+        }
+        if self.stations.len() == 1 {
+            return Some(self.on_course(0, 0.0));
+        }
+        let course = self
+            .stations
+            .partition_point(|s| s.measured_depth <= md)
+            .clamp(1, self.stations.len() - 1);
+        let s1 = &self.stations[course - 1];
+        let s2 = &self.stations[course];
+        let fraction = (md - s1.measured_depth) / (s2.measured_depth - s1.measured_depth);
+        Some(self.on_course(course - 1, fraction))
+    }
+
+    /// Stations every `step` ft from the first station, always including the
+    /// last one. A non-positive step returns just the end points.
+    pub fn every(&self, step: f64) -> Vec<PathStation> {
+        let (start, end) = (self.start_md(), self.end_md());
+        let mut out = Vec::new();
+        if step > 0.0 {
+            let count = ((end - start) / step).floor() as usize;
+            out.extend((0..=count).filter_map(|i| self.at(start + i as f64 * step)));
+        } else {
+            out.extend(self.at(start));
+        }
+        if out.last().is_none_or(|s| s.measured_depth < end) {
+            out.extend(self.at(end));
+        }
+        out
+    }
+
+    /// The point on the bore closest to `point`, with its measured depth.
+    pub fn nearest_md(&self, point: &Coordinate3D) -> NearestStation {
+        let distance = |s: &PathStation| {
+            ((s.north - point.north).powi(2)
+                + (s.east - point.east).powi(2)
+                + (s.tvd - point.tvd).powi(2))
+            .sqrt()
+        };
+        if self.stations.len() == 1 {
+            let station = self.on_course(0, 0.0);
+            return NearestStation {
+                distance: distance(&station),
+                station,
+            };
+        }
+
+        let mut best = (0, 0.0, f64::INFINITY);
+        for course in 0..self.stations.len() - 1 {
+            for i in 0..=NEAREST_SAMPLES {
+                let f = i as f64 / NEAREST_SAMPLES as f64;
+                let d = distance(&self.on_course(course, f));
+                if d < best.2 {
+                    best = (course, f, d);
+                }
+            }
+        }
+
+        // Golden-section search around the best coarse sample.
+        let (course, f, _) = best;
+        let half = 1.0 / NEAREST_SAMPLES as f64;
+        let (mut lo, mut hi) = ((f - half).max(0.0), (f + half).min(1.0));
+        let ratio = (5f64.sqrt() - 1.0) / 2.0;
+        for _ in 0..60 {
+            let a = hi - ratio * (hi - lo);
+            let b = lo + ratio * (hi - lo);
+            if distance(&self.on_course(course, a)) < distance(&self.on_course(course, b)) {
+                hi = b;
+            } else {
+                lo = a;
+            }
+        }
+        let station = self.on_course(course, (lo + hi) / 2.0);
+        NearestStation {
+            distance: distance(&station),
+            station,
+        }
+    }
+
+    /// Point at `fraction` (0..=1) of the way along course `index`.
+    fn on_course(&self, index: usize, fraction: f64) -> PathStation {
+        let s1 = &self.stations[index];
+        let p1 = &self.coords[index];
+        let Some(s2) = self.stations.get(index + 1) else {
+            return PathStation {
+                measured_depth: s1.measured_depth,
+                north: p1.north,
+                east: p1.east,
+                tvd: p1.tvd,
+                pitch: s1.inclination,
+                azimuth: s1.azimuth,
+            };
+        };
+        let d_md = s2.measured_depth - s1.measured_depth;
+        let t1 = tangent(s1.inclination, s1.azimuth);
+        let t2 = tangent(s2.inclination, s2.azimuth);
+        let cos_beta = (0..3).map(|k| t1[k] * t2[k]).sum::<f64>().clamp(-1.0, 1.0);
+        let beta = cos_beta.acos();
+
+        // Weights of t1 and t2 in the tangent and in the displacement so far.
+        let ((w1, w2), (d1, d2)) = if beta < 1e-6 {
+            (
+                (1.0 - fraction, fraction),
+                (
+                    fraction - fraction * fraction / 2.0,
+                    fraction * fraction / 2.0,
+                ),
+            )
+        } else {
+            let sin_beta = beta.sin();
+            (
+                (
+                    ((1.0 - fraction) * beta).sin() / sin_beta,
+                    (fraction * beta).sin() / sin_beta,
+                ),
+                (
+                    (((1.0 - fraction) * beta).cos() - cos_beta) / (beta * sin_beta),
+                    (1.0 - (fraction * beta).cos()) / (beta * sin_beta),
+                ),
+            )
+        };
+        let t: Vec<f64> = (0..3).map(|k| w1 * t1[k] + w2 * t2[k]).collect();
+        let offset: Vec<f64> = (0..3).map(|k| d_md * (d1 * t1[k] + d2 * t2[k])).collect();
+
+        let horizontal = t[0].hypot(t[1]);
+        let azimuth = if horizontal < 1e-12 {
+            s1.azimuth
+        } else {
+            t[1].atan2(t[0]).to_degrees().rem_euclid(360.0)
+        };
+        PathStation {
+            measured_depth: s1.measured_depth + fraction * d_md,
+            north: p1.north + offset[0],
+            east: p1.east + offset[1],
+            tvd: p1.tvd + offset[2],
+            pitch: t[2].atan2(horizontal).to_degrees(),
+            azimuth,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn station(md: f64, pitch: f64, azimuth: f64) -> SurveyPoint {
+        SurveyPoint {
+            measured_depth: md,
+            inclination: pitch,
+            azimuth,
+        }
+    }
+
+    fn bore() -> Vec<SurveyPoint> {
+        vec![
+            station(0.0, 12.0, 88.0),
+            station(60.0, 6.0, 90.0),
+            station(150.0, 0.0, 92.0),
+            station(240.0, -8.0, 95.0),
+        ]
+    }
+
+    #[test]
+    fn test_matches_stations_and_arc() {
+        let surveys = bore();
+        let path = SurveyPath::new(&surveys).unwrap();
+        let coords = calculate_path(&surveys).unwrap();
+        for (s, c) in surveys.iter().zip(&coords) {
+            let at = path.at(s.measured_depth).unwrap();
+            assert!((at.north - c.north).abs() < 1e-9);
+            assert!((at.tvd - c.tvd).abs() < 1e-9);
+            assert!((at.pitch - s.inclination).abs() < 1e-9);
+            assert!((at.azimuth - s.azimuth).abs() < 1e-9);
+        }
+
+        // Splitting a course at its midpoint gives the same end position.
+        let mid = path.at(105.0).unwrap();
+        let split = [
+            surveys[1].clone(),
+            station(105.0, mid.pitch, mid.azimuth),
+            surveys[2].clone(),
+        ];
+        let resurveyed = calculate_path(&split).unwrap();
+        assert!((resurveyed[2].north - (coords[2].north - coords[1].north)).abs() < 1e-9);
+        assert!((resurveyed[2].tvd - (coords[2].tvd - coords[1].tvd)).abs() < 1e-9);
+        assert!(path.at(-1.0).is_none());
+        assert!(path.at(240.1).is_none());
+    }
+
+    #[test]
+    fn test_every_foot() {
+        let path = SurveyPath::new(&bore()).unwrap();
+        let profile = path.every(1.0);
+        assert_eq!(profile.len(), 241);
+        assert_eq!(profile[100].measured_depth, 100.0);
+        assert_eq!(path.every(7.0).last().unwrap().measured_depth, 240.0);
+    }
+
+    #[test]
+    fn test_nearest_md_inverse() {
+        let path = SurveyPath::new(&bore()).unwrap();
+        let target = path.at(173.25).unwrap();
+        let nearest = path.nearest_md(&Coordinate3D {
+            north: target.north,
+            east: target.east,
+            tvd: target.tvd,
+        });
+        assert!((nearest.station.measured_depth - 173.25).abs() < 1e-4);
+        assert!(nearest.distance < 1e-4);
+
+        // A crossing 10 ft below the bore is 10 ft away at about the same MD.
+        let below = path.nearest_md(&Coordinate3D {
+            north: target.north,
+            east: target.east,
+            tvd: target.tvd + 10.0,
+        });
+        assert!((below.distance - 10.0).abs() < 0.1);
+        assert!((below.station.measured_depth - 173.25).abs() < 2.0);
+    }
+}
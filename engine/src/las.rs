@@ -0,0 +1,409 @@
+use crate::mnemonics::MnemonicMap;
+use crate::witsml_parser::{
+    parse_cells, resolve_columns, Column, LogCurveInfo, TelemetryData, WitsmlError,
+    DEFAULT_NULL_VALUE,
+};
+use std::fmt::Write;
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum LasError {
+    #[error("Line {line}: {message}")]
+    Syntax { line: usize, message: String },
+    #[error("Unsupported LAS version '{0}'")]
+    UnsupportedVersion(String),
+    #[error("LAS file has no data section")]
+    MissingData,
+    #[error("Wrapped data ends mid-row ({0} values left over)")]
+    TruncatedRow(usize),
+    #[error("Row {row}: '{value}' contains the column delimiter")]
+    DelimiterInValue { row: usize, value: String },
+    #[error(transparent)]
+    Telemetry(#[from] WitsmlError),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LasVersion {
+    V2,
+    V3,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Section {
+    Version,
+    Well,
+    Curve,
+    Data,
+    Other,
+}
+
+impl Section {
+    /// Classifies a `~` line. LAS 3.0 names its log sections
+    /// `~Log_Definition`/`~Log_Data`; other data sets (core, tops, ...) are
+    /// skipped.
+    fn from_header(line: &str) -> Self {
+        let name = line[1..]
+            .split(|c: char| c.is_whitespace() || c == '|')
+            .next()
+            .unwrap_or("")
+            .to_ascii_uppercase();
+        match name.as_str() {
+            "LOG_DEFINITION" => return Section::Curve,
+            "LOG_DATA" => return Section::Data,
+            n if n.contains('_') => return Section::Other,
+            _ => {}
+        }
+        match name.chars().next() {
+            Some('V') => Section::Version,
+            Some('W') => Section::Well,
+            Some('C') => Section::Curve,
+            Some('A') => Section::Data,
+            _ => Section::Other,
+        }
+    }
+}
+
+/// `MNEM.UNIT  VALUE : DESCRIPTION`
+struct HeaderLine<'a> {
+    mnemonic: &'a str,
+    unit: &'a str,
+    value: &'a str,
+}
+
+fn parse_header_line(line: &str, number: usize) -> Result<HeaderLine<'_>, LasError> {
+    let syntax = |message: &str| LasError::Syntax {
+        line: number,
+        message: message.to_string(),
+    };
+    let dot = line
+        .find('.')
+        .ok_or_else(|| syntax("missing '.' after mnemonic"))?;
+    let rest = &line[dot + 1..];
+    let unit_end = rest
+        .find(|c: char| c.is_whitespace() || c == ':')
+        .unwrap_or(rest.len());
+    let after_unit = &rest[unit_end..];
+    let colon = after_unit
+        .rfind(':')
+        .ok_or_else(|| syntax("missing ':' before description"))?;
+    Ok(HeaderLine {
+        mnemonic: line[..dot].trim(),
+        unit: &rest[..unit_end],
+        value: after_unit[..colon].trim(),
+    })
+}
+
+fn check_version(value: &str) -> Result<(), LasError> {
+    match value.parse::<f64>() {
+        Ok(v) if v == 2.0 || v == 3.0 => Ok(()),
+        _ => Err(LasError::UnsupportedVersion(value.to_string())),
+    }
+}
+
+/// LAS files commonly write feet as `F`.
+fn normalise_unit(unit: &str) -> &str {
+    if unit.eq_ignore_ascii_case("F") {
+        "ft"
+    } else {
+        unit
+    }
+}
+
+/// Parses a LAS 2.0 or 3.0 file into telemetry rows using the built-in
+/// mnemonic aliases.
+pub fn parse_las(text: &str) -> Result<Vec<TelemetryData>, LasError> {
+    parse_las_with(text, &MnemonicMap::default())
+}
+
+/// Parses a LAS 2.0 or 3.0 file, mapping curves through `mnemonics`.
+///
+/// Handles wrapped (`WRAP. YES`) data, the `~Well` `NULL` value and the LAS
+/// 3.0 `DLM` delimiter. Values are converted to canonical units from the
+/// curve units in `~Curve`/`~Log_Definition`.
+pub fn parse_las_with(text: &str, mnemonics: &MnemonicMap) -> Result<Vec<TelemetryData>, LasError> {
+    let mut section = Section::Other;
+    let mut wrapped = false;
+    let mut delimiter = None;
+    let mut null_value: Option<String> = None;
+    let mut curves: Vec<LogCurveInfo> = Vec::new();
+    let mut columns: Option<Vec<Column>> = None;
+    let mut pending: Vec<String> = Vec::new();
+    let mut rows = Vec::new();
+
+    for (index, raw) in text.lines().enumerate() {
+        let number = index + 1;
+        let line = raw.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        if line.starts_with('~') {
+            section = Section::from_header(line);
+            if section == Section::Data && columns.is_none() {
+                columns = Some(resolve_columns(
+                    std::mem::take(&mut curves),
+                    null_value.as_deref(),
+                    mnemonics,
+                )?);
+            }
+            continue;
+        }
+
+        match section {
+            Section::Version => {
+                let item = parse_header_line(line, number)?;
+                match item.mnemonic.to_ascii_uppercase().as_str() {
+                    "VERS" => check_version(item.value)?,
+                    "WRAP" => wrapped = item.value.eq_ignore_ascii_case("YES"),
+                    "DLM" => {
+                        delimiter = match item.value.to_ascii_uppercase().as_str() {
+                            "COMMA" => Some(','),
+                            "TAB" => Some('\t'),
+                            _ => None,
+                        }
+                    }
+                    _ => {}
+                }
+            }
+            Section::Well => {
+                let item = parse_header_line(line, number)?;
+                if item.mnemonic.eq_ignore_ascii_case("NULL") {
+                    null_value = Some(item.value.to_string());
+                }
+            }
+            Section::Curve => {
+                let item = parse_header_line(line, number)?;
+                curves.push(LogCurveInfo {
+                    mnemonic: item.mnemonic.to_string(),
+                    unit: normalise_unit(item.unit).to_string(),
+                    null_value: None,
+                });
+            }
+            Section::Data => {
+                let columns = columns.as_deref().unwrap_or_default();
+                let cells: Vec<&str> = match delimiter {
+                    Some(d) => raw.split(d).map(str::trim).collect(),
+                    None => raw.split_whitespace().collect(),
+                };
+                if !wrapped {
+                    rows.push(parse_cells(&cells, rows.len(), columns)?);
+                    continue;
+                }
+                pending.extend(cells.iter().map(|c| c.to_string()));
+                while !columns.is_empty() && pending.len() >= columns.len() {
+                    let row: Vec<String> = pending.drain(..columns.len()).collect();
+                    let cells: Vec<&str> = row.iter().map(String::as_str).collect();
+                    rows.push(parse_cells(&cells, rows.len(), columns)?);
+                }
+            }
+            Section::Other => {}
+        }
+    }
+
+    if columns.is_none() {
+        return Err(LasError::MissingData);
+    }
+    if !pending.is_empty() {
+        return Err(LasError::TruncatedRow(pending.len()));
+    }
+    Ok(rows)
+}
+
+/// Writes telemetry as an unwrapped LAS file indexed by depth.
+///
+/// Values are written in canonical units with `-999.25` for missing
+/// readings, and DEPT is always the first curve. LAS 2.0 data must be
+/// numeric, so timestamps are left out; LAS 3.0 files are comma-delimited
+/// and carry a TIME curve after DEPT when some row has a timestamp. Fails if
+/// a timestamp contains a comma.
+pub fn write_las(
+    rows: &[TelemetryData],
+    well_name: &str,
+    version: LasVersion,
+) -> Result<String, LasError> {
+    let has_time = version == LasVersion::V3 && rows.iter().any(|r| !r.timestamp.is_empty());
+    let mut out = String::new();
+
+    let (vers, curve_header, data_header, delimiter) = match version {
+        LasVersion::V2 => ("2.0", "~Curve Information", "~ASCII", " "),
+        LasVersion::V3 => ("3.0", "~Log_Definition", "~Log_Data | Log_Definition", ", "),
+    };
+    out.push_str("~Version Information\n");
+    header(&mut out, "VERS", "", vers, "CWLS LOG ASCII STANDARD");
+    header(&mut out, "WRAP", "", "NO", "One line per depth step");
+    if version == LasVersion::V3 {
+        header(&mut out, "DLM", "", "COMMA", "Column delimiter");
+    }
+
+    let start = rows.first().map_or(DEFAULT_NULL_VALUE, |r| r.depth);
+    let stop = rows.last().map_or(DEFAULT_NULL_VALUE, |r| r.depth);
+    out.push_str("~Well Information\n");
+    header(&mut out, "STRT", "FT", &start.to_string(), "START DEPTH");
+    header(&mut out, "STOP", "FT", &stop.to_string(), "STOP DEPTH");
+    header(
+        &mut out,
+        "STEP",
+        "FT",
+        &depth_step(rows).to_string(),
+        "STEP",
+    );
+    header(
+        &mut out,
+        "NULL",
+        "",
+        &DEFAULT_NULL_VALUE.to_string(),
+        "NULL VALUE",
+    );
+    header(&mut out, "WELL", "", well_name, "WELL");
+
+    out.push_str(curve_header);
+    out.push('\n');
+    header(&mut out, "DEPT", "FT", "", "Measured depth");
+    if has_time {
+        header(&mut out, "TIME", "", "", "Timestamp");
+    }
+    header(&mut out, "TRQ", "FT.LBF", "", "Rotary torque");
+    header(&mut out, "THRUST", "LBF", "", "Thrust/pullback force");
+    header(&mut out, "FLOW", "GAL/MIN", "", "Mud flow");
+    header(&mut out, "RPM", "RPM", "", "Bit rotation speed");
+
+    out.push_str(data_header);
+    out.push('\n');
+    let null = DEFAULT_NULL_VALUE.to_string();
+    let value = |v: Option<f64>| v.map_or_else(|| null.clone(), |v| v.to_string());
+    for (index, row) in rows.iter().enumerate() {
+        let mut cells = vec![row.depth.to_string()];
+        if has_time {
+            if row.timestamp.contains(',') {
+                return Err(LasError::DelimiterInValue {
+                    row: index,
+                    value: row.timestamp.clone(),
+                });
+            }
+            cells.push(if row.timestamp.is_empty() {
+                null.clone()
+            } else {
+                row.timestamp.clone()
+            });
+        }
+        cells.extend([
+            value(row.torque),
+            value(row.thrust),
+            value(row.mud_flow),
+            value(row.bit_rpm),
+        ]);
+        out.push_str(&cells.join(delimiter));
+        out.push('\n');
+    }
+    Ok(out)
+}
+
+fn header(out: &mut String, mnemonic: &str, unit: &str, value: &str, description: &str) {
+    let _ = writeln!(
+        out,
+        " {:<14} {:>24} : {}",
+        format!("{}.{}", mnemonic, unit),
+        value,
+        description
+    );
+}
+
+/// Constant depth spacing, or 0 for irregular logs as LAS prescribes.
+fn depth_step(rows: &[TelemetryData]) -> f64 {
+    let mut steps = rows.windows(2).map(|w| w[1].depth - w[0].depth);
+    let Some(first) = steps.next() else {
+        return 0.0;
+    };
+    if steps.all(|s| (s - first).abs() < 1e-6) {
+        first
+    } else {
+        0.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const V2_WRAPPED: &str = include_str!("../testdata/bore_v2_wrapped.las");
+    const V3_COMMA: &str = include_str!("../testdata/bore_v3.las");
+
+    #[test]
+    fn test_parse_wrapped_v2() {
+        let rows = parse_las(V2_WRAPPED).unwrap();
+        assert_eq!(rows.len(), 3);
+        assert_eq!(rows[0].depth, 100.0);
+        assert_eq!(rows[0].torque, Some(1500.0));
+        assert_eq!(rows[1].thrust, None);
+        assert_eq!(rows[2].bit_rpm, Some(65.0));
+    }
+
+    #[test]
+    fn test_parse_v3_metric() {
+        let rows = parse_las(V3_COMMA).unwrap();
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[0].timestamp, "2024-05-01T10:00:00Z");
+        assert!((rows[0].depth - 100.0).abs() < 1e-9);
+        assert!((rows[0].thrust.unwrap() - 2248.08943).abs() < 1e-4);
+        assert_eq!(rows[1].mud_flow, None);
+    }
+
+    #[test]
+    fn test_round_trip() {
+        for sample in [V2_WRAPPED, V3_COMMA] {
+            let rows = parse_las(sample).unwrap();
+            let written = write_las(&rows, "BORE-1", LasVersion::V3).unwrap();
+            assert_eq!(parse_las(&written).unwrap(), rows);
+
+            // LAS 2.0 keeps the numeric curves only.
+            let written = write_las(&rows, "BORE-1", LasVersion::V2).unwrap();
+            let untimed: Vec<_> = rows
+                .iter()
+                .map(|r| TelemetryData {
+                    timestamp: String::new(),
+                    ..r.clone()
+                })
+                .collect();
+            assert_eq!(parse_las(&written).unwrap(), untimed);
+        }
+    }
+
+    #[test]
+    fn test_written_layout() {
+        let mut rows = parse_las(V3_COMMA).unwrap();
+        rows[0].timestamp = "2024-05-01 10:00:00".into();
+        let written = write_las(&rows, "BORE-1", LasVersion::V3).unwrap();
+        assert_eq!(parse_las(&written).unwrap(), rows);
+        assert!(written.contains(" DLM."));
+        let curves: Vec<&str> = written
+            .lines()
+            .skip_while(|l| !l.starts_with("~Log_Definition"))
+            .skip(1)
+            .take(2)
+            .map(|l| l.trim().split('.').next().unwrap())
+            .collect();
+        assert_eq!(curves, ["DEPT", "TIME"]);
+
+        let v2 = write_las(&rows, "BORE-1", LasVersion::V2).unwrap();
+        assert!(!v2.contains("TIME"));
+        let first_curve = v2.lines().skip_while(|l| !l.starts_with("~Curve")).nth(1);
+        assert!(first_curve.unwrap().trim().starts_with("DEPT.FT"));
+
+        rows[1].timestamp = "May 1, 2024".into();
+        assert!(matches!(
+            write_las(&rows, "BORE-1", LasVersion::V3),
+            Err(LasError::DelimiterInValue { row: 1, .. })
+        ));
+    }
+
+    #[test]
+    fn test_bad_value_reports_row() {
+        let text = V2_WRAPPED.replace("1510.0", "bad");
+        assert!(matches!(
+            parse_las(&text),
+            Err(LasError::Telemetry(WitsmlError::InvalidValue {
+                row: 1,
+                ..
+            }))
+        ));
+    }
+}
@@ -1,20 +1,123 @@
 pub mod witsml_parser;
+pub mod witsml_stream;
+pub mod witsml_writer;
+pub mod archive;
+pub mod clearance;
+pub mod deviation;
+pub mod georeference;
 pub mod hdd_physics;
+pub mod interpolate;
+pub mod las;
+pub mod log_accumulator;
+pub mod lwd_csv;
+pub mod mnemonics;
+pub mod north_reference;
+pub mod profile_design;
+pub mod resample;
+pub mod rig_state;
+pub mod rod_pass;
+pub mod steering;
+pub mod timestamp;
+pub mod uncertainty;
+pub mod uom;
+pub mod validation;
+pub mod wits0;
 
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+#[derive(Debug, Clone, PartialEq)]
 pub struct SurveyPoint {
     pub measured_depth: f64,
     pub inclination: f64,
     pub azimuth: f64,
 }
 
+#[derive(Debug, Clone, PartialEq)]
 pub struct Coordinate3D {
     pub north: f64,
     pub east: f64,
     pub tvd: f64, // True Vertical Depth
 }
 
+#[derive(Error, Debug, Clone, PartialEq)]
+pub enum SurveyError {
+    #[error("No survey stations")]
+    Empty,
+    #[error("Station {index}: {field} is not a finite number")]
+    NotFinite { index: usize, field: &'static str },
+    #[error("Station {index}: measured depth {measured_depth} does not increase on the previous station")]
+    NonIncreasingDepth { index: usize, measured_depth: f64 },
+    #[error("Station {index}: pitch {pitch} is outside -90..=90 degrees")]
+    PitchOutOfRange { index: usize, pitch: f64 },
+    #[error("Station {index}: azimuth {azimuth} is outside 0..=360 degrees")]
+    AzimuthOutOfRange { index: usize, azimuth: f64 },
+}
+
+/// Checks stations before they go into a path calculation.
+pub fn validate_stations(surveys: &[SurveyPoint]) -> Result<(), SurveyError> {
+    if surveys.is_empty() {
+        return Err(SurveyError::Empty);
+    }
+    for (index, s) in surveys.iter().enumerate() {
+        for (field, value) in [
+            ("measured depth", s.measured_depth),
+            ("pitch", s.inclination),
+            ("azimuth", s.azimuth),
+        ] {
+            if !value.is_finite() {
+                return Err(SurveyError::NotFinite { index, field });
+            }
+        }
+        if !(-90.0..=90.0).contains(&s.inclination) {
+            return Err(SurveyError::PitchOutOfRange {
+                index,
+                pitch: s.inclination,
+            });
+        }
+        if !(0.0..=360.0).contains(&s.azimuth) {
+            return Err(SurveyError::AzimuthOutOfRange {
+                index,
+                azimuth: s.azimuth,
+            });
+        }
+        if index > 0 && s.measured_depth <= surveys[index - 1].measured_depth {
+            return Err(SurveyError::NonIncreasingDepth {
+                index,
+                measured_depth: s.measured_depth,
+            });
+        }
+    }
+    Ok(())
+}
+
+/// Survey calculation method used to turn stations into a path.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SurveyMethod {
+    #[default]
+    MinimumCurvature,
+    RadiusOfCurvature,
+    BalancedTangential,
+    AverageAngle,
+    /// Uses only the lower station's angles over each course.
+    Tangential,
+}
+
 /// Calculates the bore path using the Minimum Curvature Method.
-pub fn calculate_path(surveys: &[SurveyPoint]) -> Vec<Coordinate3D> {
+///
+/// Returns one coordinate per station, relative to the first station.
+pub fn calculate_path(surveys: &[SurveyPoint]) -> Result<Vec<Coordinate3D>, SurveyError> {
+    calculate_path_with(surveys, SurveyMethod::MinimumCurvature)
+}
+
+/// Calculates the bore path with the given survey method.
+pub fn calculate_path_with(
+    surveys: &[SurveyPoint],
+    method: SurveyMethod,
+) -> Result<Vec<Coordinate3D>, SurveyError> {
+    validate_stations(surveys)?;
+
     let mut path = Vec::new();
     let mut current_n = 0.0;
     let mut current_e = 0.0;
@@ -23,32 +126,8 @@ pub fn calculate_path(surveys: &[SurveyPoint]) -> Vec<Coordinate3D> {
     // Add start point (0,0,0)
     path.push(Coordinate3D { north: 0.0, east: 0.0, tvd: 0.0 });
 
-    for i in 0..surveys.len() - 1 {
-        let p1 = &surveys[i];
-        let p2 = &surveys[i+1];
-
-        let d_md = p2.measured_depth - p1.measured_depth;
-        
-        // Convert HDD Pitch (0=Horizontal) to Inclination (0=Vertical)
-        // Inc = 90 - Pitch
-        let i1 = (90.0 - p1.inclination).to_radians();
-        let i2 = (90.0 - p2.inclination).to_radians();
-        
-        let a1 = p1.azimuth.to_radians();
-        let a2 = p2.azimuth.to_radians();
-
-        // Dogleg angle (beta)
-        let beta = (i1.cos() * i2.cos() + i1.sin() * i2.sin() * (a2 - a1).cos()).acos();
-
-        let rf = if beta.abs() < 1e-6 {
-            1.0 // Straight line approximation for very small angles
-        } else {
-            (2.0 / beta) * (beta / 2.0).tan()
-        };
-
-        let delta_n = (d_md / 2.0) * (i1.sin() * a1.cos() + i2.sin() * a2.cos()) * rf;
-        let delta_e = (d_md / 2.0) * (i1.sin() * a1.sin() + i2.sin() * a2.sin()) * rf;
-        let delta_tvd = (d_md / 2.0) * (i1.cos() + i2.cos()) * rf;
+    for pair in surveys.windows(2) {
+        let (delta_n, delta_e, delta_tvd) = course_displacement(&pair[0], &pair[1], method);
 
         current_n += delta_n;
         current_e += delta_e;
@@ -61,7 +140,85 @@ pub fn calculate_path(surveys: &[SurveyPoint]) -> Vec<Coordinate3D> {
         });
     }
 
-    path
+    Ok(path)
+}
+
+/// North, east and TVD change over the course between two stations.
+pub(crate) fn course_displacement(
+    p1: &SurveyPoint,
+    p2: &SurveyPoint,
+    method: SurveyMethod,
+) -> (f64, f64, f64) {
+    let d_md = p2.measured_depth - p1.measured_depth;
+
+    // Convert HDD Pitch (0=Horizontal) to Inclination (0=Vertical)
+    // Inc = 90 - Pitch
+    let i1 = (90.0 - p1.inclination).to_radians();
+    let i2 = (90.0 - p2.inclination).to_radians();
+
+    let a1 = p1.azimuth.to_radians();
+    let a2 = p2.azimuth.to_radians();
+    // Shortest turn, so 359 -> 1 is a 2 degree turn rather than 358.
+    let d_az = (p2.azimuth - p1.azimuth + 540.0).rem_euclid(360.0).to_radians()
+        - std::f64::consts::PI;
+
+    match method {
+        SurveyMethod::MinimumCurvature | SurveyMethod::BalancedTangential => {
+            let rf = if method == SurveyMethod::BalancedTangential {
+                1.0
+            } else {
+                // Dogleg angle (beta). Rounding can push the cosine of nearly
+                // collinear stations just past 1, so clamp to avoid NaN.
+                let cos_beta = i1.cos() * i2.cos() + i1.sin() * i2.sin() * (a2 - a1).cos();
+                let beta = cos_beta.clamp(-1.0, 1.0).acos();
+                if beta.abs() < 1e-6 {
+                    1.0 // Straight line approximation for very small angles
+                } else {
+                    (2.0 / beta) * (beta / 2.0).tan()
+                }
+            };
+            (
+                (d_md / 2.0) * (i1.sin() * a1.cos() + i2.sin() * a2.cos()) * rf,
+                (d_md / 2.0) * (i1.sin() * a1.sin() + i2.sin() * a2.sin()) * rf,
+                (d_md / 2.0) * (i1.cos() + i2.cos()) * rf,
+            )
+        }
+        SurveyMethod::AverageAngle => {
+            let inc = (i1 + i2) / 2.0;
+            let az = a1 + d_az / 2.0;
+            (
+                d_md * inc.sin() * az.cos(),
+                d_md * inc.sin() * az.sin(),
+                d_md * inc.cos(),
+            )
+        }
+        SurveyMethod::Tangential => (
+            d_md * i2.sin() * a2.cos(),
+            d_md * i2.sin() * a2.sin(),
+            d_md * i2.cos(),
+        ),
+        SurveyMethod::RadiusOfCurvature => {
+            // Each factor falls back to its limit when the inclination or
+            // azimuth doesn't change over the course.
+            let d_inc = i2 - i1;
+            let (horizontal, vertical) = if d_inc.abs() < 1e-9 {
+                (i1.sin(), i1.cos())
+            } else {
+                ((i1.cos() - i2.cos()) / d_inc, (i2.sin() - i1.sin()) / d_inc)
+            };
+            let a2 = a1 + d_az;
+            let (north, east) = if d_az.abs() < 1e-9 {
+                (a1.cos(), a1.sin())
+            } else {
+                ((a2.sin() - a1.sin()) / d_az, (a1.cos() - a2.cos()) / d_az)
+            };
+            (
+                d_md * horizontal * north,
+                d_md * horizontal * east,
+                d_md * vertical,
+            )
+        }
+    }
 }
 
 #[cfg(test)]
@@ -74,7 +231,7 @@ mod tests {
             SurveyPoint { measured_depth: 0.0, inclination: 0.0, azimuth: 0.0 },
             SurveyPoint { measured_depth: 100.0, inclination: 0.0, azimuth: 0.0 },
         ];
-        let path = calculate_path(&surveys);
+        let path = calculate_path(&surveys).unwrap();
         assert_eq!(path.len(), 2);
         assert!((path[1].north - 100.0).abs() < 1e-6); // Should go 100ft North (if Inc=0 is horizontal North? Wait, Inc=0 is usually vertical in drilling, but in HDD Inc=0 is usually horizontal level. Let's assume Inc=0 is Level, Az=0 is North).
         // Actually, standard drilling: Inc 0 = Vertical Down. Inc 90 = Horizontal.
@@ -87,4 +244,187 @@ mod tests {
         // HDD inputs are usually Pitch (0 = Horizontal).
         // We need a conversion function: Inclination = 90 - Pitch.
     }
+
+    #[test]
+    fn test_invalid_surveys() {
+        let station = |md: f64, pitch: f64, az: f64| SurveyPoint {
+            measured_depth: md,
+            inclination: pitch,
+            azimuth: az,
+        };
+        assert_eq!(calculate_path(&[]), Err(SurveyError::Empty));
+        assert!(matches!(
+            calculate_path(&[station(0.0, 0.0, 0.0), station(0.0, 1.0, 0.0)]),
+            Err(SurveyError::NonIncreasingDepth { index: 1, .. })
+        ));
+        assert!(matches!(
+            calculate_path(&[station(0.0, 95.0, 0.0)]),
+            Err(SurveyError::PitchOutOfRange { index: 0, .. })
+        ));
+        assert!(matches!(
+            calculate_path(&[station(0.0, 0.0, -1.0)]),
+            Err(SurveyError::AzimuthOutOfRange { index: 0, .. })
+        ));
+        assert_eq!(
+            calculate_path(&[station(0.0, 0.0, 0.0), station(f64::NAN, 0.0, 0.0)]),
+            Err(SurveyError::NotFinite {
+                index: 1,
+                field: "measured depth"
+            })
+        );
+        assert_eq!(calculate_path(&[station(5.0, 0.0, 0.0)]).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_nearly_collinear_stations_stay_finite() {
+        let surveys: Vec<_> = (0..200)
+            .map(|i| SurveyPoint {
+                measured_depth: i as f64 * 10.0,
+                inclination: -12.345_678_9,
+                azimuth: 87.654_321 + i as f64 * 1e-9,
+            })
+            .collect();
+        let path = calculate_path(&surveys).unwrap();
+        assert!(path
+            .iter()
+            .all(|c| c.north.is_finite() && c.east.is_finite() && c.tvd.is_finite()));
+    }
+
+    /// Inclination 10 -> 20 degrees, azimuth 30 -> 40 degrees over 100 ft
+    /// (pitch 80 -> 70 in the HDD convention). Regression values; see
+    /// `test_methods_match_path_definitions` for the independent check.
+    #[test]
+    fn test_methods_worked_example() {
+        let surveys = [
+            SurveyPoint { measured_depth: 1000.0, inclination: 80.0, azimuth: 30.0 },
+            SurveyPoint { measured_depth: 1100.0, inclination: 70.0, azimuth: 40.0 },
+        ];
+        let cases = [
+            (SurveyMethod::MinimumCurvature, [20.6750, 15.3749, 96.4847]),
+            (SurveyMethod::RadiusOfCurvature, [21.1475, 14.8076, 96.4700]),
+            (SurveyMethod::BalancedTangential, [20.6193, 15.3335, 96.2250]),
+            (SurveyMethod::AverageAngle, [21.2012, 14.8453, 96.5926]),
+            (SurveyMethod::Tangential, [26.2003, 21.9846, 93.9693]),
+        ];
+        for (method, [north, east, tvd]) in cases {
+            let end = &calculate_path_with(&surveys, method).unwrap()[1];
+            assert!((end.north - north).abs() < 1e-3, "{:?} north {}", method, end.north);
+            assert!((end.east - east).abs() < 1e-3, "{:?} east {}", method, end.east);
+            assert!((end.tvd - tvd).abs() < 1e-3, "{:?} tvd {}", method, end.tvd);
+        }
+    }
+
+    /// Integrates the well path each method assumes between two stations
+    /// (Bourgoyne et al., Applied Drilling Engineering, SPE Textbook Series
+    /// vol. 2, 1986, ch. 8), rather than evaluating its closed form:
+    /// minimum curvature follows the circular arc through both tangents,
+    /// radius of curvature lies on a cylinder (inclination linear in MD,
+    /// azimuth linear in horizontal departure),
+    /// and the tangential family averages station tangents or angles.
+    #[test]
+    fn test_methods_match_path_definitions() {
+        // Unit tangent (north, east, down) from conventional inclination.
+        fn tangent(inclination: f64, azimuth: f64) -> [f64; 3] {
+            let (i, a) = (inclination.to_radians(), azimuth.to_radians());
+            [i.sin() * a.cos(), i.sin() * a.sin(), i.cos()]
+        }
+        // Simpson's rule over a course, for a tangent given at 0..=1.
+        fn integrate(length: f64, t: impl Fn(f64) -> [f64; 3]) -> [f64; 3] {
+            let n = 2000;
+            let mut sum = [0.0; 3];
+            for k in 0..=n {
+                let weight = if k == 0 || k == n { 1.0 } else if k % 2 == 1 { 4.0 } else { 2.0 };
+                let v = t(k as f64 / n as f64);
+                for (s, v) in sum.iter_mut().zip(v) {
+                    *s += weight * v;
+                }
+            }
+            sum.map(|s| s * length / (3.0 * n as f64))
+        }
+
+        // (md, inclination, azimuth) pairs, including the widely used
+        // 3500/3600 ft course from 15/20 to 25/45 degrees.
+        let courses = [
+            ((1000.0, 10.0, 30.0), (1100.0, 20.0, 40.0)),
+            ((3500.0, 15.0, 20.0), (3600.0, 25.0, 45.0)),
+            ((0.0, 40.0, 350.0), (90.0, 55.0, 20.0)),
+        ];
+        for ((md1, i1, a1), (md2, i2, a2)) in courses {
+            let surveys = [
+                SurveyPoint { measured_depth: md1, inclination: 90.0 - i1, azimuth: a1 },
+                SurveyPoint { measured_depth: md2, inclination: 90.0 - i2, azimuth: a2 },
+            ];
+            let length = md2 - md1;
+            let (t1, t2) = (tangent(i1, a1), tangent(i2, a2));
+            let beta = (0..3).map(|k| t1[k] * t2[k]).sum::<f64>().acos();
+            let d_az = (a2 - a1 + 180.0).rem_euclid(360.0) - 180.0;
+            let expected = [
+                (
+                    SurveyMethod::MinimumCurvature,
+                    integrate(length, |f| {
+                        let (w1, w2) = (((1.0 - f) * beta).sin(), (f * beta).sin());
+                        [0, 1, 2].map(|k| (w1 * t1[k] + w2 * t2[k]) / beta.sin())
+                    }),
+                ),
+                {
+                    let inclination = |f: f64| (i1 + f * (i2 - i1)).to_radians();
+                    let departure = integrate(length, |f| [inclination(f).sin(), 0.0, 0.0])[0];
+                    let tvd = integrate(length, |f| [inclination(f).cos(), 0.0, 0.0])[0];
+                    let plan = integrate(departure, |f| {
+                        let a = (a1 + f * d_az).to_radians();
+                        [a.cos(), a.sin(), 0.0]
+                    });
+                    (SurveyMethod::RadiusOfCurvature, [plan[0], plan[1], tvd])
+                },
+                (
+                    SurveyMethod::BalancedTangential,
+                    [0, 1, 2].map(|k| length * (t1[k] + t2[k]) / 2.0),
+                ),
+                (
+                    SurveyMethod::AverageAngle,
+                    tangent((i1 + i2) / 2.0, a1 + d_az / 2.0).map(|v| v * length),
+                ),
+                (SurveyMethod::Tangential, t2.map(|v| v * length)),
+            ];
+            for (method, [north, east, tvd]) in expected {
+                let end = &calculate_path_with(&surveys, method).unwrap()[1];
+                assert!((end.north - north).abs() < 1e-6, "{:?} north {} vs {}", method, end.north, north);
+                assert!((end.east - east).abs() < 1e-6, "{:?} east {} vs {}", method, end.east, east);
+                assert!((end.tvd - tvd).abs() < 1e-6, "{:?} tvd {} vs {}", method, end.tvd, tvd);
+            }
+        }
+    }
+
+    /// A quarter circle of 1000 ft radius from vertical to horizontal: the
+    /// curvature methods reproduce the arc exactly, the others don't.
+    #[test]
+    fn test_methods_on_circular_arc() {
+        let surveys = [
+            SurveyPoint { measured_depth: 0.0, inclination: 90.0, azimuth: 0.0 },
+            SurveyPoint {
+                measured_depth: 1000.0 * std::f64::consts::FRAC_PI_2,
+                inclination: 0.0,
+                azimuth: 0.0,
+            },
+        ];
+        for method in [SurveyMethod::MinimumCurvature, SurveyMethod::RadiusOfCurvature] {
+            let end = &calculate_path_with(&surveys, method).unwrap()[1];
+            assert!((end.north - 1000.0).abs() < 1e-6, "{:?}", method);
+            assert!((end.tvd - 1000.0).abs() < 1e-6, "{:?}", method);
+            assert!(end.east.abs() < 1e-9);
+        }
+        let end = &calculate_path_with(&surveys, SurveyMethod::BalancedTangential).unwrap()[1];
+        assert!((end.north - 785.398).abs() < 1e-3);
+    }
+
+    #[test]
+    fn test_average_angle_across_north() {
+        let surveys = [
+            SurveyPoint { measured_depth: 0.0, inclination: 0.0, azimuth: 350.0 },
+            SurveyPoint { measured_depth: 100.0, inclination: 0.0, azimuth: 10.0 },
+        ];
+        let end = &calculate_path_with(&surveys, SurveyMethod::AverageAngle).unwrap()[1];
+        assert!((end.north - 100.0).abs() < 1e-9);
+        assert!(end.east.abs() < 1e-9);
+    }
 }
@@ -0,0 +1,254 @@
+use crate::mnemonics::MnemonicMap;
+use crate::timestamp::{parse_timestamp, NaiveTimes};
+use crate::witsml_parser::{
+    parse_log_fragments, LogFragment, LogIndexType, TelemetryData, WitsmlError,
+};
+use chrono::{DateTime, Utc};
+use std::collections::{BTreeMap, HashMap};
+
+/// Depth indices closer than this (ft) are treated as the same row.
+const DEPTH_RESOLUTION: f64 = 0.001;
+
+/// A position in a log, in the log's own index.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LogIndex {
+    Depth(f64),
+    Time(DateTime<Utc>),
+}
+
+impl LogIndex {
+    fn index_type(&self) -> LogIndexType {
+        match self {
+            LogIndex::Depth(_) => LogIndexType::MeasuredDepth,
+            LogIndex::Time(_) => LogIndexType::DateTime,
+        }
+    }
+
+    /// Ordered key: depth in thousandths of a foot, time in milliseconds.
+    fn key(&self) -> i64 {
+        match self {
+            LogIndex::Depth(depth) => (depth / DEPTH_RESOLUTION).round() as i64,
+            LogIndex::Time(time) => time.timestamp_millis(),
+        }
+    }
+}
+
+/// Result of merging one fragment into the accumulator.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AppendSummary {
+    pub uid: String,
+    /// Rows at indices not seen before.
+    pub inserted: usize,
+    /// Rows that overwrote an existing row at the same index.
+    pub replaced: usize,
+}
+
+struct GrowingLog {
+    index_type: LogIndexType,
+    rows: BTreeMap<i64, TelemetryData>,
+}
+
+/// Merges successive transmissions of growing WITSML logs (`objectGrowing`).
+///
+/// Rows are keyed by their index, so overlapping `startIndex`/`endIndex`
+/// ranges collapse to one row per index (the latest transmission wins) and
+/// fragments may arrive in any order.
+pub struct LogAccumulator {
+    mnemonics: MnemonicMap,
+    naive_times: NaiveTimes,
+    logs: HashMap<String, GrowingLog>,
+}
+
+impl Default for LogAccumulator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl LogAccumulator {
+    pub fn new() -> Self {
+        Self::with_mnemonics(MnemonicMap::default())
+    }
+
+    pub fn with_mnemonics(mnemonics: MnemonicMap) -> Self {
+        Self {
+            mnemonics,
+            naive_times: NaiveTimes::default(),
+            logs: HashMap::new(),
+        }
+    }
+
+    /// Places date-time indices without an offset by `naive_times`; they
+    /// are read as UTC otherwise.
+    pub fn with_naive_times(mut self, naive_times: NaiveTimes) -> Self {
+        self.naive_times = naive_times;
+        self
+    }
+
+    /// Parses a `<log>`/`<logs>` document and merges every log in it.
+    pub fn append_xml(&mut self, xml: &str) -> Result<Vec<AppendSummary>, WitsmlError> {
+        parse_log_fragments(xml, &self.mnemonics)?
+            .into_iter()
+            .map(|fragment| self.append(fragment))
+            .collect()
+    }
+
+    /// Merges one fragment. Nothing is merged if any row fails to index.
+    pub fn append(&mut self, fragment: LogFragment) -> Result<AppendSummary, WitsmlError> {
+        let uid = fragment.uid.ok_or(WitsmlError::MissingUid)?;
+
+        let naive_times = self.naive_times;
+        let keyed = fragment
+            .rows
+            .into_iter()
+            .enumerate()
+            .map(|(row, data)| {
+                let index = row_index(&data, row, fragment.index_type, naive_times)?;
+                Ok((index.key(), data))
+            })
+            .collect::<Result<Vec<_>, WitsmlError>>()?;
+
+        let log = self.logs.entry(uid.clone()).or_insert_with(|| GrowingLog {
+            index_type: fragment.index_type,
+            rows: BTreeMap::new(),
+        });
+        if log.index_type != fragment.index_type {
+            return Err(WitsmlError::IndexMismatch {
+                uid,
+                expected: log.index_type,
+            });
+        }
+
+        let mut summary = AppendSummary {
+            uid,
+            inserted: 0,
+            replaced: 0,
+        };
+        for (key, data) in keyed {
+            match log.rows.insert(key, data) {
+                Some(_) => summary.replaced += 1,
+                None => summary.inserted += 1,
+            }
+        }
+        Ok(summary)
+    }
+
+    pub fn uids(&self) -> impl Iterator<Item = &str> {
+        self.logs.keys().map(String::as_str)
+    }
+
+    /// All rows of a log in index order.
+    pub fn rows(&self, uid: &str) -> Vec<&TelemetryData> {
+        self.logs
+            .get(uid)
+            .map(|log| log.rows.values().collect())
+            .unwrap_or_default()
+    }
+
+    /// Rows strictly after `since`, in index order. Unknown uids yield no rows.
+    pub fn rows_since(
+        &self,
+        uid: &str,
+        since: LogIndex,
+    ) -> Result<Vec<&TelemetryData>, WitsmlError> {
+        let Some(log) = self.logs.get(uid) else {
+            return Ok(Vec::new());
+        };
+        if log.index_type != since.index_type() {
+            return Err(WitsmlError::IndexMismatch {
+                uid: uid.to_string(),
+                expected: log.index_type,
+            });
+        }
+        Ok(log.rows.range(since.key() + 1..).map(|(_, d)| d).collect())
+    }
+
+    /// The last index received for a log, for polling "what's new" queries.
+    pub fn end_index(&self, uid: &str) -> Option<LogIndex> {
+        let log = self.logs.get(uid)?;
+        let (_, last) = log.rows.last_key_value()?;
+        row_index(last, 0, log.index_type, self.naive_times).ok()
+    }
+}
+
+fn row_index(
+    data: &TelemetryData,
+    row: usize,
+    index_type: LogIndexType,
+    naive_times: NaiveTimes,
+) -> Result<LogIndex, WitsmlError> {
+    match index_type {
+        LogIndexType::MeasuredDepth => Ok(LogIndex::Depth(data.depth)),
+        LogIndexType::DateTime => parse_timestamp(&data.timestamp, naive_times)
+            .map(LogIndex::Time)
+            .ok_or_else(|| WitsmlError::InvalidValue {
+                row,
+                column: "timestamp".to_string(),
+                value: data.timestamp.clone(),
+            }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fragment(uid: &str, rows: &[&str]) -> String {
+        let data: String = rows.iter().map(|r| format!("<data>{}</data>", r)).collect();
+        format!(
+            r#"<logs><log uid="{}">
+                <indexType>measured depth</indexType>
+                <logCurveInfo><mnemonic>DEPT</mnemonic><unit>ft</unit></logCurveInfo>
+                <logCurveInfo><mnemonic>TRQ</mnemonic><unit>ft.lbf</unit></logCurveInfo>
+                <logData>{}</logData>
+            </log></logs>"#,
+            uid, data
+        )
+    }
+
+    #[test]
+    fn test_overlap_and_out_of_order() {
+        let mut acc = LogAccumulator::new();
+        acc.append_xml(&fragment("log-1", &["10,100", "20,200", "30,300"]))
+            .unwrap();
+        // Next transmission overlaps at 30 ft with a revised value.
+        let summary = acc
+            .append_xml(&fragment("log-1", &["30,310", "40,400"]))
+            .unwrap();
+        assert_eq!(summary[0].inserted, 1);
+        assert_eq!(summary[0].replaced, 1);
+        acc.append_xml(&fragment("log-1", &["5,50"])).unwrap();
+
+        let depths: Vec<f64> = acc.rows("log-1").iter().map(|d| d.depth).collect();
+        assert_eq!(depths, vec![5.0, 10.0, 20.0, 30.0, 40.0]);
+        assert_eq!(acc.end_index("log-1"), Some(LogIndex::Depth(40.0)));
+
+        let since = acc.rows_since("log-1", LogIndex::Depth(20.0)).unwrap();
+        assert_eq!(since.len(), 2);
+        assert_eq!(since[0].torque, Some(310.0));
+    }
+
+    #[test]
+    fn test_time_indexed_log() {
+        let xml = r#"<log uid="rt-1">
+            <indexType>date time</indexType>
+            <logCurveInfo><mnemonic>TIME</mnemonic></logCurveInfo>
+            <logCurveInfo><mnemonic>DEPT</mnemonic><unit>ft</unit></logCurveInfo>
+            <logData>
+                <data>2024-05-01T10:00:01Z,101</data>
+                <data>2024-05-01T10:00:00Z,100</data>
+            </logData>
+        </log>"#;
+        let mut acc = LogAccumulator::new();
+        acc.append_xml(xml).unwrap();
+
+        let since: DateTime<Utc> = "2024-05-01T10:00:00Z".parse().unwrap();
+        let rows = acc.rows_since("rt-1", LogIndex::Time(since)).unwrap();
+        assert_eq!(rows.len(), 1);
+        assert_eq!(rows[0].depth, 101.0);
+        assert!(matches!(
+            acc.rows_since("rt-1", LogIndex::Depth(0.0)),
+            Err(WitsmlError::IndexMismatch { .. })
+        ));
+    }
+}
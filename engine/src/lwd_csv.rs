@@ -0,0 +1,456 @@
+use crate::uom::{self, Quantity, Unit};
+use crate::witsml_parser::TelemetryData;
+use crate::SurveyPoint;
+use std::io::Read;
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum LwdError {
+    #[error("CSV Error: {0}")]
+    Csv(#[from] csv::Error),
+    #[error("Unrecognized LWD header: {0}")]
+    UnrecognizedHeader(String),
+    #[error("Row {row}, column {column}: cannot parse '{value}'")]
+    InvalidValue {
+        row: usize,
+        column: String,
+        value: String,
+    },
+    #[error("Row {row}: no measured depth (length or rod number)")]
+    MissingMeasuredDepth { row: usize },
+    #[error("Column '{column}' has unsupported unit: {source}")]
+    Unit {
+        column: String,
+        source: crate::witsml_parser::WitsmlError,
+    },
+}
+
+/// Export layouts recognised from the header row.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LwdLayout {
+    /// DCI LWD export: one row per rod, rod length set for the whole job.
+    DciLwd,
+    /// DigiTrak data-logger export: carries the length of each rod.
+    Digitrak,
+}
+
+/// Job settings the CSV itself does not carry.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LwdImportOptions {
+    /// Rod length in ft, used when the file has no length column.
+    pub rod_length_ft: f64,
+    /// Azimuth of the bore line in degrees. Walkover locators do not measure
+    /// azimuth, so every station is given this heading.
+    pub bore_azimuth: f64,
+}
+
+impl Default for LwdImportOptions {
+    fn default() -> Self {
+        Self {
+            rod_length_ft: 10.0,
+            bore_azimuth: 0.0,
+        }
+    }
+}
+
+/// One locate reading, in feet and degrees.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LwdRecord {
+    pub rod: Option<u32>,
+    pub measured_depth: f64,
+    /// Pitch in degrees as reported by the locator (positive = nose up).
+    pub pitch: Option<f64>,
+    /// Depth of cover below the locator, in ft.
+    pub depth_of_cover: Option<f64>,
+    pub roll: Option<f64>,
+    pub temperature_f: Option<f64>,
+    pub timestamp: String,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct LwdImport {
+    pub layout: LwdLayout,
+    pub records: Vec<LwdRecord>,
+    pub telemetry: Vec<TelemetryData>,
+    pub surveys: Vec<SurveyPoint>,
+}
+
+/// Column positions resolved from the header row.
+#[derive(Debug, Default)]
+struct HeaderMap {
+    rod: Option<usize>,
+    rod_length: Option<(usize, Unit)>,
+    length: Option<(usize, Unit)>,
+    pitch: Option<(usize, bool)>,
+    depth: Option<(usize, Unit)>,
+    roll: Option<usize>,
+    temperature: Option<(usize, bool)>,
+    timestamp: Option<usize>,
+    date: Option<usize>,
+    time: Option<usize>,
+}
+
+/// Lower-cases a header and drops punctuation: "Pitch (%)" -> "pitch%".
+fn normalise(header: &str) -> String {
+    header
+        .chars()
+        .filter(|c| c.is_alphanumeric() || *c == '%')
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+/// Unit written after the column name, e.g. "Depth (in)", "Rod Length [m]"
+/// or "Depth In.". `name` is the normalised column name the header starts
+/// with, so spacing and punctuation inside the name don't matter.
+fn header_unit<'a>(header: &'a str, name: &str) -> &'a str {
+    for (open, close) in [('(', ')'), ('[', ']')] {
+        if let (Some(start), Some(end)) = (header.find(open), header.rfind(close)) {
+            if start < end {
+                return header[start + 1..end].trim();
+            }
+        }
+    }
+    let mut remaining = name.chars().count();
+    let name_end = header
+        .char_indices()
+        .find(|(_, c)| {
+            if remaining == 0 {
+                return true;
+            }
+            if c.is_alphanumeric() || *c == '%' {
+                remaining -= 1;
+            }
+            false
+        })
+        .map_or(header.len(), |(i, _)| i);
+    header[name_end..]
+        .trim_start_matches(|c: char| !c.is_alphanumeric())
+        .trim_end_matches('.')
+        .trim()
+}
+
+fn length_unit(header: &str, name: &str, default: &str) -> Result<Unit, LwdError> {
+    let unit = match header_unit(header, name) {
+        "" => default,
+        unit => unit,
+    };
+    uom::lookup_for(unit, Quantity::Length).map_err(|source| LwdError::Unit {
+        column: header.to_string(),
+        source,
+    })
+}
+
+impl HeaderMap {
+    fn detect(headers: &csv::StringRecord) -> Result<(Self, LwdLayout), LwdError> {
+        let mut map = HeaderMap::default();
+        for (i, raw) in headers.iter().enumerate() {
+            let raw = raw.trim();
+            let name = normalise(raw);
+            match name.as_str() {
+                "rod" | "rodno" | "rodnumber" => map.rod = Some(i),
+                "datetime" | "timestamp" => map.timestamp = Some(i),
+                "date" => map.date = Some(i),
+                "time" => map.time = Some(i),
+                n if n.starts_with("rodlength") => {
+                    map.rod_length = Some((i, length_unit(raw, "rodlength", "ft")?))
+                }
+                n if n.starts_with("length") => {
+                    map.length = Some((i, length_unit(raw, "length", "ft")?))
+                }
+                n if n.starts_with("distance") => {
+                    map.length = Some((i, length_unit(raw, "distance", "ft")?))
+                }
+                n if n.starts_with("pitch") => {
+                    let degrees = n.contains("deg") && !n.contains('%');
+                    map.pitch = Some((i, degrees))
+                }
+                n if n.starts_with("depth") => {
+                    map.depth = Some((i, length_unit(raw, "depth", "in")?))
+                }
+                n if n.starts_with("roll") || n.starts_with("clock") => map.roll = Some(i),
+                n if n.starts_with("temp") => {
+                    let celsius = n.ends_with('c') || raw.contains("°C");
+                    map.temperature = Some((i, celsius))
+                }
+                _ => {}
+            }
+        }
+
+        if map.pitch.is_none() || (map.rod.is_none() && map.length.is_none()) {
+            let header: Vec<&str> = headers.iter().collect();
+            return Err(LwdError::UnrecognizedHeader(header.join(",")));
+        }
+        let layout = if map.rod_length.is_some() {
+            LwdLayout::Digitrak
+        } else {
+            LwdLayout::DciLwd
+        };
+        Ok((map, layout))
+    }
+}
+
+fn cell(record: &csv::StringRecord, index: Option<usize>) -> Option<&str> {
+    index
+        .and_then(|i| record.get(i))
+        .map(str::trim)
+        .filter(|c| !c.is_empty())
+}
+
+fn number(
+    record: &csv::StringRecord,
+    headers: &csv::StringRecord,
+    index: Option<usize>,
+    row: usize,
+) -> Result<Option<f64>, LwdError> {
+    let Some(value) = cell(record, index) else {
+        return Ok(None);
+    };
+    let value = value.trim_end_matches('%');
+    value
+        .parse::<f64>()
+        .map(Some)
+        .map_err(|_| LwdError::InvalidValue {
+            row,
+            column: index
+                .and_then(|i| headers.get(i))
+                .unwrap_or_default()
+                .to_string(),
+            value: value.to_string(),
+        })
+}
+
+/// Imports a DCI LWD or DigiTrak CSV export.
+///
+/// The layout is detected from the header row. Pitch percent is converted to
+/// degrees and cover depths (inches unless the header says otherwise) to
+/// feet. Measured depth comes from a length column if present, otherwise from
+/// the rod number times the rod length; a row that gives neither, or a rod
+/// number that isn't a whole non-negative number, fails the import.
+///
+/// Survey points use the HDD convention of `calculate_path` (positive pitch
+/// descends), so the locator's nose-up-positive pitch is negated. If the
+/// first reading is past the entry, an entry station at MD 0 with the first
+/// pitch is prepended.
+pub fn import_lwd_csv<R: Read>(
+    reader: R,
+    options: &LwdImportOptions,
+) -> Result<LwdImport, LwdError> {
+    let mut csv = csv::ReaderBuilder::new().flexible(true).from_reader(reader);
+    let headers = csv.headers()?.clone();
+    let (map, layout) = HeaderMap::detect(&headers)?;
+
+    let mut records = Vec::new();
+    let mut cumulative_length = 0.0;
+    for (row, result) in csv.records().enumerate() {
+        let record = result?;
+        if record.iter().all(|c| c.trim().is_empty()) {
+            continue;
+        }
+
+        let rod = match number(&record, &headers, map.rod, row)? {
+            Some(r) if r >= 0.0 && r.fract() == 0.0 && r <= u32::MAX as f64 => Some(r as u32),
+            Some(_) => {
+                return Err(LwdError::InvalidValue {
+                    row,
+                    column: map
+                        .rod
+                        .and_then(|i| headers.get(i))
+                        .unwrap_or_default()
+                        .to_string(),
+                    value: cell(&record, map.rod).unwrap_or_default().to_string(),
+                })
+            }
+            None => None,
+        };
+        let measured_depth = if let Some((i, unit)) = map.length {
+            number(&record, &headers, Some(i), row)?.map(|v| unit.to_canonical(v))
+        } else if let Some((i, unit)) = map.rod_length {
+            let length = number(&record, &headers, Some(i), row)?
+                .map_or(options.rod_length_ft, |v| unit.to_canonical(v));
+            cumulative_length += length;
+            Some(cumulative_length)
+        } else {
+            rod.map(|r| r as f64 * options.rod_length_ft)
+        };
+        let Some(measured_depth) = measured_depth else {
+            return Err(LwdError::MissingMeasuredDepth { row });
+        };
+
+        let pitch =
+            number(&record, &headers, map.pitch.map(|(i, _)| i), row)?.map(|p| match map.pitch {
+                Some((_, true)) => p,
+                _ => (p / 100.0).atan().to_degrees(),
+            });
+        let depth_of_cover = match map.depth {
+            Some((i, unit)) => {
+                number(&record, &headers, Some(i), row)?.map(|v| unit.to_canonical(v))
+            }
+            None => None,
+        };
+        let temperature_f = match map.temperature {
+            Some((i, celsius)) => number(&record, &headers, Some(i), row)?.map(|t| {
+                if celsius {
+                    t * 9.0 / 5.0 + 32.0
+                } else {
+                    t
+                }
+            }),
+            None => None,
+        };
+        let timestamp = match (
+            cell(&record, map.timestamp),
+            cell(&record, map.date),
+            cell(&record, map.time),
+        ) {
+            (Some(ts), _, _) => ts.to_string(),
+            (None, Some(date), Some(time)) => format!("{} {}", date, time),
+            (None, Some(date), None) => date.to_string(),
+            (None, None, Some(time)) => time.to_string(),
+            (None, None, None) => String::new(),
+        };
+
+        records.push(LwdRecord {
+            rod,
+            measured_depth,
+            pitch,
+            depth_of_cover,
+            roll: number(&record, &headers, map.roll, row)?,
+            temperature_f,
+            timestamp,
+        });
+    }
+
+    let telemetry = records
+        .iter()
+        .map(|r| TelemetryData {
+            timestamp: r.timestamp.clone(),
+            depth: r.measured_depth,
+            torque: None,
+            thrust: None,
+            mud_flow: None,
+            bit_rpm: None,
+        })
+        .collect();
+
+    let mut surveys: Vec<SurveyPoint> = records
+        .iter()
+        .filter_map(|r| {
+            r.pitch.map(|pitch| SurveyPoint {
+                measured_depth: r.measured_depth,
+                inclination: -pitch,
+                azimuth: options.bore_azimuth,
+            })
+        })
+        .collect();
+    if let Some(first) = surveys.first() {
+        if first.measured_depth > 0.0 {
+            let entry = SurveyPoint {
+                measured_depth: 0.0,
+                ..first.clone()
+            };
+            surveys.insert(0, entry);
+        }
+    }
+
+    Ok(LwdImport {
+        layout,
+        records,
+        telemetry,
+        surveys,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dci_lwd_layout() {
+        let csv = "\
+Rod,Pitch (%),Depth (in),Roll,Temp (F),Date,Time
+1,-20.0,24,3,72,2024-05-01,10:00:00
+2,-15.0,48,4,73,2024-05-01,10:04:10
+3,0,66,,74,2024-05-01,10:08:30
+";
+        let import = import_lwd_csv(csv.as_bytes(), &LwdImportOptions::default()).unwrap();
+        assert_eq!(import.layout, LwdLayout::DciLwd);
+        assert_eq!(import.records.len(), 3);
+
+        let first = &import.records[0];
+        assert_eq!(first.measured_depth, 10.0);
+        assert!((first.pitch.unwrap() - (-11.309932)).abs() < 1e-6);
+        assert_eq!(first.depth_of_cover, Some(2.0));
+        assert_eq!(first.timestamp, "2024-05-01 10:00:00");
+        assert_eq!(import.records[2].roll, None);
+
+        assert_eq!(import.telemetry[1].depth, 20.0);
+        assert_eq!(import.surveys.len(), 4);
+        assert_eq!(import.surveys[0].measured_depth, 0.0);
+        assert!(import.surveys[1].inclination > 0.0);
+    }
+
+    #[test]
+    fn test_digitrak_layout() {
+        let csv = "\
+Rod #,Rod Length (ft),Pitch %,Depth In.,Roll,Temperature (C)
+1,15,-18,30,2,20
+2,15,-10,60,2,21
+";
+        let import = import_lwd_csv(csv.as_bytes(), &LwdImportOptions::default()).unwrap();
+        assert_eq!(import.layout, LwdLayout::Digitrak);
+        assert_eq!(import.records[1].measured_depth, 30.0);
+        assert_eq!(import.records[1].depth_of_cover, Some(5.0));
+        assert_eq!(import.records[0].temperature_f, Some(68.0));
+
+        // Units are read after the name however the name is spaced.
+        let csv = "\
+Rod_No,RodLength[m],Pitch %,Depth_cm
+1,3,-18,30
+2,3,-10,60
+";
+        let import = import_lwd_csv(csv.as_bytes(), &LwdImportOptions::default()).unwrap();
+        assert!((import.records[1].measured_depth - 6.0 / 0.3048).abs() < 1e-9);
+        assert!((import.records[1].depth_of_cover.unwrap() - 0.6 / 0.3048).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_header_units() {
+        assert_eq!(header_unit("Rod Length (ft)", "rodlength"), "ft");
+        assert_eq!(header_unit("Rod Length ft", "rodlength"), "ft");
+        assert_eq!(header_unit("Depth In.", "depth"), "In");
+        assert_eq!(header_unit("Distance", "distance"), "");
+        assert!(matches!(
+            length_unit("Depth furlongs", "depth", "in"),
+            Err(LwdError::Unit { .. })
+        ));
+    }
+
+    #[test]
+    fn test_bad_rods_and_missing_depth() {
+        let options = LwdImportOptions::default();
+        for rod in ["-3", "2.7"] {
+            let csv = format!("Rod,Pitch (%)\n1,-20\n{},-15\n", rod);
+            match import_lwd_csv(csv.as_bytes(), &options) {
+                Err(LwdError::InvalidValue { row, column, value }) => {
+                    assert_eq!((row, column.as_str(), value.as_str()), (1, "Rod", rod));
+                }
+                other => panic!("unexpected result: {:?}", other),
+            }
+        }
+
+        let csv = "Rod,Pitch (%)\n1,-20\n,-15\n";
+        assert!(matches!(
+            import_lwd_csv(csv.as_bytes(), &options),
+            Err(LwdError::MissingMeasuredDepth { row: 1 })
+        ));
+    }
+
+    #[test]
+    fn test_unrecognized_header() {
+        let csv = "Station,Northing,Easting\n1,2,3\n";
+        assert!(matches!(
+            import_lwd_csv(csv.as_bytes(), &LwdImportOptions::default()),
+            Err(LwdError::UnrecognizedHeader(_))
+        ));
+    }
+}
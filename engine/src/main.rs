@@ -1,19 +1,38 @@
 use axum::{
+    extract::State,
+    http::StatusCode,
     routing::{get, post},
     Router,
     Json,
 };
 use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
 use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
 use tower_http::cors::CorsLayer;
-use engine::{calculate_path, SurveyPoint};
+use engine::deviation::{deviation_report, DeviationReport, ToleranceTube};
+use engine::north_reference::{calculate_path_in, AzimuthCorrection, NorthReference};
+use engine::{SurveyMethod, SurveyPoint};
+use engine::wits0::{serve_wits0, Wits0Map};
+use engine::witsml_parser::TelemetryData;
+
+/// Most recent WITS0 samples kept for `/api/realtime/wits0`.
+const WITS0_BUFFER: usize = 10_000;
+
+type RealtimeRows = Arc<Mutex<VecDeque<TelemetryData>>>;
 
 #[tokio::main]
 async fn main() {
+    let realtime: RealtimeRows = Arc::default();
+    spawn_wits0_listener(realtime.clone()).await;
+
     // Define routes
     let app = Router::new()
         .route("/health", get(health_check))
         .route("/api/calculate/mcm", post(calculate_mcm))
+        .route("/api/calculate/deviation", post(calculate_deviation))
+        .route("/api/realtime/wits0", get(wits0_rows))
+        .with_state(realtime)
         .layer(CorsLayer::permissive());
 
     // Run server
@@ -24,6 +43,58 @@ async fn main() {
     axum::serve(listener, app).await.unwrap();
 }
 
+/// Listens for WITS0 feeds on `WITS0_ADDR` (default 127.0.0.1:7001), using
+/// the item map in `WITS0_MAP` if set.
+async fn spawn_wits0_listener(rows: RealtimeRows) {
+    let addr = std::env::var("WITS0_ADDR").unwrap_or_else(|_| "127.0.0.1:7001".to_string());
+    let map = match std::env::var("WITS0_MAP") {
+        Ok(path) => match std::fs::read_to_string(&path)
+            .map_err(engine::wits0::Wits0Error::from)
+            .and_then(|s| Wits0Map::from_toml_str(&s))
+        {
+            Ok(map) => map,
+            Err(e) => {
+                eprintln!("WITS0 map {}: {}", path, e);
+                return;
+            }
+        },
+        Err(_) => Wits0Map::default(),
+    };
+    let listener = match tokio::net::TcpListener::bind(&addr).await {
+        Ok(listener) => listener,
+        Err(e) => {
+            eprintln!("WITS0 listener not started on {}: {}", addr, e);
+            return;
+        }
+    };
+    println!("WITS0 listener on {}", addr);
+
+    let (tx, mut rx) = tokio::sync::mpsc::channel(1024);
+    tokio::spawn(async move {
+        if let Err(e) = serve_wits0(listener, map, tx).await {
+            eprintln!("WITS0 listener stopped: {}", e);
+        }
+    });
+    tokio::spawn(async move {
+        while let Some(result) = rx.recv().await {
+            match result {
+                Ok(row) => {
+                    let mut rows = rows.lock().unwrap();
+                    if rows.len() >= WITS0_BUFFER {
+                        rows.pop_front();
+                    }
+                    rows.push_back(row);
+                }
+                Err(e) => eprintln!("WITS0: {}", e),
+            }
+        }
+    });
+}
+
+async fn wits0_rows(State(rows): State<RealtimeRows>) -> Json<Vec<TelemetryData>> {
+    Json(rows.lock().unwrap().iter().cloned().collect())
+}
+
 async fn health_check() -> &'static str {
     "Rust Engine is running!"
 }
@@ -31,6 +102,21 @@ async fn health_check() -> &'static str {
 #[derive(Deserialize)]
 struct McmRequest {
     surveys: Vec<SurveyInput>,
+    /// Defaults to minimum curvature.
+    #[serde(default)]
+    method: SurveyMethod,
+    /// North the input azimuths are measured from. Defaults to true north.
+    #[serde(default)]
+    azimuth_reference: NorthReference,
+    /// North the returned path is aligned to. Defaults to true north.
+    #[serde(default)]
+    output_reference: NorthReference,
+    /// Magnetic declination in degrees, east positive.
+    #[serde(default)]
+    declination: f64,
+    /// WGS84 entry coordinate, used for UTM 15N grid convergence.
+    entry_lat: Option<f64>,
+    entry_lon: Option<f64>,
 }
 
 #[derive(Deserialize)]
@@ -52,24 +138,53 @@ struct PathPoint {
     tvd: f64,
 }
 
-async fn calculate_mcm(Json(payload): Json<McmRequest>) -> Json<McmResponse> {
-    // Convert input to SurveyPoint
-    let points: Vec<SurveyPoint> = payload.surveys.iter().map(|s| SurveyPoint {
-        measured_depth: s.md,
-        inclination: s.pitch, // Note: lib.rs expects Pitch (0=Horizontal) based on comments? 
-                              // Let's check lib.rs again.
-                              // Line 34: let i1 = (90.0 - p1.inclination).to_radians();
-                              // If p1.inclination is Pitch (0), then i1 = 90 (Vertical).
-                              // If p1.inclination is 90 (Vertical down), i1 = 0.
-                              // The formula uses sin(i1).
-                              // If i1=90 (Horizontal in formula view?), sin(90)=1.
-                              // Wait, standard MCM uses Inclination where 0=Vertical.
-                              // If lib.rs does (90 - input), it assumes input is Pitch (0=Horizontal).
-                              // So we pass Pitch directly.
-        azimuth: s.az,
-    }).collect();
+#[derive(Serialize)]
+struct ErrorResponse {
+    error: String,
+}
 
-    let result = calculate_path(&points);
+type ApiError = (StatusCode, Json<ErrorResponse>);
+
+/// 422 for requests that parse but can't be calculated.
+fn unprocessable(error: impl std::fmt::Display) -> ApiError {
+    (
+        StatusCode::UNPROCESSABLE_ENTITY,
+        Json(ErrorResponse {
+            error: error.to_string(),
+        }),
+    )
+}
+
+async fn calculate_mcm(
+    Json(payload): Json<McmRequest>,
+) -> Result<Json<McmResponse>, ApiError> {
+    let points = to_survey_points(&payload.surveys);
+
+    let uses_grid = payload.azimuth_reference == NorthReference::Grid
+        || payload.output_reference == NorthReference::Grid;
+    let correction = match (payload.entry_lat, payload.entry_lon) {
+        (Some(lat), Some(lon)) => AzimuthCorrection::for_utm15n(payload.declination, lat, lon),
+        (None, None) if !uses_grid => AzimuthCorrection {
+            declination: payload.declination,
+            grid_convergence: 0.0,
+        },
+        _ => {
+            let error = if uses_grid {
+                "grid north needs both entry_lat and entry_lon for the grid convergence"
+            } else {
+                "entry_lat and entry_lon must be given together"
+            };
+            return Err(unprocessable(error));
+        }
+    };
+    let result = calculate_path_in(
+        &points,
+        payload.method,
+        &correction,
+        payload.azimuth_reference,
+        payload.output_reference,
+    )
+    .map_err(unprocessable)?;
 
     let path_response: Vec<PathPoint> = result.iter().map(|p| PathPoint {
         north: p.north,
@@ -77,7 +192,39 @@ async fn calculate_mcm(Json(payload): Json<McmRequest>) -> Json<McmResponse> {
         tvd: p.tvd,
     }).collect();
 
-    Json(McmResponse {
+    Ok(Json(McmResponse {
         path: path_response,
-    })
+    }))
+}
+
+#[derive(Deserialize)]
+struct DeviationRequest {
+    actual: Vec<SurveyInput>,
+    planned: Vec<SurveyInput>,
+    /// Defaults to a 2 ft tube.
+    #[serde(default)]
+    tolerance: ToleranceTube,
+}
+
+fn to_survey_points(inputs: &[SurveyInput]) -> Vec<SurveyPoint> {
+    inputs
+        .iter()
+        .map(|s| SurveyPoint {
+            measured_depth: s.md,
+            inclination: s.pitch,
+            azimuth: s.az,
+        })
+        .collect()
+}
+
+async fn calculate_deviation(
+    Json(payload): Json<DeviationRequest>,
+) -> Result<Json<DeviationReport>, ApiError> {
+    deviation_report(
+        &to_survey_points(&payload.actual),
+        &to_survey_points(&payload.planned),
+        &payload.tolerance,
+    )
+    .map(Json)
+    .map_err(unprocessable)
 }
@@ -0,0 +1,262 @@
+use crate::uom::Quantity;
+use crate::witsml_parser::WitsmlError;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::Path;
+
+/// The `TelemetryData` field a log curve feeds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Channel {
+    Timestamp,
+    Depth,
+    Torque,
+    Thrust,
+    MudFlow,
+    BitRpm,
+}
+
+impl Channel {
+    /// Quantity a numeric channel is measured in; `None` for the time index.
+    pub fn quantity(self) -> Option<Quantity> {
+        match self {
+            Channel::Timestamp => None,
+            Channel::Depth => Some(Quantity::Length),
+            Channel::Torque => Some(Quantity::Torque),
+            Channel::Thrust => Some(Quantity::Force),
+            Channel::MudFlow => Some(Quantity::FlowRate),
+            Channel::BitRpm => Some(Quantity::RotationalSpeed),
+        }
+    }
+}
+
+/// Rig and guidance-system vendors with built-in alias sets.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Vendor {
+    /// Standard WITSML mnemonics (DEPT, TRQ, WOB, ...).
+    Generic,
+    Vermeer,
+    DitchWitch,
+    AmericanAugers,
+    Dci,
+    Subsite,
+}
+
+impl Vendor {
+    pub const ALL: [Vendor; 6] = [
+        Vendor::Generic,
+        Vendor::Vermeer,
+        Vendor::DitchWitch,
+        Vendor::AmericanAugers,
+        Vendor::Dci,
+        Vendor::Subsite,
+    ];
+
+    /// Channel names used in the vendor's standard data export.
+    fn aliases(self) -> &'static [(Channel, &'static [&'static str])] {
+        match self {
+            Vendor::Generic => &[
+                (Channel::Timestamp, &["TIME", "DATETIME"]),
+                (Channel::Depth, &["DEPT", "BITDEP", "DMEA"]),
+                (Channel::Torque, &["TQA", "TRQ"]),
+                (Channel::Thrust, &["WOB", "THRUST"]),
+                (Channel::MudFlow, &["MFIA", "FLOW"]),
+                (Channel::BitRpm, &["RPMA", "RPM"]),
+            ],
+            Vendor::Vermeer => &[
+                (Channel::Depth, &["ROD_DEPTH", "BORE_LENGTH"]),
+                (Channel::Torque, &["ROTATION_TORQUE", "ROT_TRQ"]),
+                (Channel::Thrust, &["THRUST_PULLBACK", "CARRIAGE_FORCE"]),
+                (Channel::MudFlow, &["MUD_FLOW", "DRILL_FLUID_FLOW"]),
+                (Channel::BitRpm, &["ROTATION_SPEED", "SPINDLE_RPM"]),
+            ],
+            Vendor::DitchWitch => &[
+                (Channel::Depth, &["BORELENGTH", "PIPE_LENGTH"]),
+                (Channel::Torque, &["ROTARYTORQUE", "RTORQ"]),
+                (Channel::Thrust, &["THRUSTFORCE", "PULLBACKFORCE"]),
+                (Channel::MudFlow, &["FLUIDFLOW", "MUDFLOW"]),
+                (Channel::BitRpm, &["ROTARYSPEED", "RRPM"]),
+            ],
+            Vendor::AmericanAugers => &[
+                (Channel::Depth, &["AA_DEPTH", "FOOTAGE"]),
+                (Channel::Torque, &["AA_TORQUE", "ROT_TORQUE"]),
+                (Channel::Thrust, &["AA_THRUST", "PUSH_PULL"]),
+                (Channel::MudFlow, &["AA_FLOW", "PUMP_FLOW"]),
+                (Channel::BitRpm, &["AA_RPM", "ROT_SPEED"]),
+            ],
+            Vendor::Dci => &[
+                (Channel::Timestamp, &["DATE_TIME", "TIMESTAMP"]),
+                (Channel::Depth, &["DCI_DEPTH", "MEASURED_DEPTH"]),
+            ],
+            Vendor::Subsite => &[
+                (Channel::Timestamp, &["TIME_STAMP"]),
+                (Channel::Depth, &["SS_DEPTH", "BORE_DIST"]),
+            ],
+        }
+    }
+}
+
+/// On-disk layout of an alias file, in TOML or JSON:
+///
+/// ```toml
+/// extends_defaults = true
+///
+/// [aliases]
+/// depth = ["HOLE_DEPTH"]
+/// torque = ["ROT_TQ"]
+/// ```
+#[derive(Debug, Deserialize)]
+struct AliasFile {
+    #[serde(default = "default_extends")]
+    extends_defaults: bool,
+    #[serde(default)]
+    aliases: HashMap<Channel, Vec<String>>,
+}
+
+fn default_extends() -> bool {
+    true
+}
+
+/// Maps vendor curve mnemonics onto `TelemetryData` fields.
+///
+/// Mnemonics are matched case-insensitively. Later entries override earlier
+/// ones, so a loaded file can remap a built-in mnemonic.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MnemonicMap {
+    aliases: HashMap<String, Channel>,
+}
+
+impl Default for MnemonicMap {
+    /// Built-in aliases for every supported vendor.
+    fn default() -> Self {
+        let mut map = Self::empty();
+        for vendor in Vendor::ALL {
+            map.add_vendor(vendor);
+        }
+        map
+    }
+}
+
+impl MnemonicMap {
+    pub fn empty() -> Self {
+        Self {
+            aliases: HashMap::new(),
+        }
+    }
+
+    /// Standard WITSML mnemonics plus the given vendor's aliases.
+    pub fn for_vendor(vendor: Vendor) -> Self {
+        let mut map = Self::empty();
+        map.add_vendor(Vendor::Generic);
+        map.add_vendor(vendor);
+        map
+    }
+
+    fn add_vendor(&mut self, vendor: Vendor) {
+        for (channel, mnemonics) in vendor.aliases() {
+            for mnemonic in mnemonics.iter() {
+                self.insert(mnemonic, *channel);
+            }
+        }
+    }
+
+    pub fn insert(&mut self, mnemonic: &str, channel: Channel) {
+        self.aliases
+            .insert(mnemonic.trim().to_ascii_uppercase(), channel);
+    }
+
+    /// Adds every alias in `other`, overriding existing entries.
+    pub fn merge(&mut self, other: &MnemonicMap) {
+        for (mnemonic, channel) in &other.aliases {
+            self.aliases.insert(mnemonic.clone(), *channel);
+        }
+    }
+
+    pub fn resolve(&self, mnemonic: &str) -> Option<Channel> {
+        self.aliases
+            .get(&mnemonic.trim().to_ascii_uppercase())
+            .copied()
+    }
+
+    pub fn from_toml_str(s: &str) -> Result<Self, WitsmlError> {
+        let file: AliasFile =
+            toml::from_str(s).map_err(|e| WitsmlError::AliasConfig(e.to_string()))?;
+        Ok(Self::from_file(file))
+    }
+
+    pub fn from_json_str(s: &str) -> Result<Self, WitsmlError> {
+        let file: AliasFile =
+            serde_json::from_str(s).map_err(|e| WitsmlError::AliasConfig(e.to_string()))?;
+        Ok(Self::from_file(file))
+    }
+
+    /// Loads an alias file, choosing TOML or JSON by extension.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, WitsmlError> {
+        let path = path.as_ref();
+        let contents = std::fs::read_to_string(path)?;
+        match path.extension().and_then(|e| e.to_str()) {
+            Some("toml") => Self::from_toml_str(&contents),
+            Some("json") => Self::from_json_str(&contents),
+            _ => Err(WitsmlError::AliasConfig(format!(
+                "unsupported alias file type: {}",
+                path.display()
+            ))),
+        }
+    }
+
+    fn from_file(file: AliasFile) -> Self {
+        let mut map = if file.extends_defaults {
+            Self::default()
+        } else {
+            Self::empty()
+        };
+        for (channel, mnemonics) in file.aliases {
+            for mnemonic in mnemonics {
+                map.insert(&mnemonic, channel);
+            }
+        }
+        map
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_vendor_defaults() {
+        let map = MnemonicMap::for_vendor(Vendor::Vermeer);
+        assert_eq!(map.resolve("rod_depth"), Some(Channel::Depth));
+        assert_eq!(map.resolve("DEPT"), Some(Channel::Depth));
+        assert_eq!(map.resolve("RTORQ"), None);
+        assert_eq!(
+            MnemonicMap::default().resolve("RTORQ"),
+            Some(Channel::Torque)
+        );
+    }
+
+    #[test]
+    fn test_load_toml_and_json() {
+        let map = MnemonicMap::from_toml_str(
+            r#"
+            extends_defaults = false
+            [aliases]
+            depth = ["HOLE_DEPTH"]
+            bit_rpm = ["SPIN"]
+            "#,
+        )
+        .unwrap();
+        assert_eq!(map.resolve("hole_depth"), Some(Channel::Depth));
+        assert_eq!(map.resolve("SPIN"), Some(Channel::BitRpm));
+        assert_eq!(map.resolve("DEPT"), None);
+
+        let map = MnemonicMap::from_json_str(r#"{"aliases": {"torque": ["TQ_X"]}}"#).unwrap();
+        assert_eq!(map.resolve("TQ_X"), Some(Channel::Torque));
+        assert_eq!(map.resolve("DEPT"), Some(Channel::Depth));
+
+        assert!(matches!(
+            MnemonicMap::from_json_str(r#"{"aliases": {"pressure": ["SPP"]}}"#),
+            Err(WitsmlError::AliasConfig(_))
+        ));
+    }
+}
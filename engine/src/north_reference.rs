@@ -0,0 +1,226 @@
+use crate::{calculate_path_with, Coordinate3D, SurveyError, SurveyMethod, SurveyPoint};
+use serde::{Deserialize, Serialize};
+
+/// Central meridian of UTM zone 15N (degrees).
+const UTM15_CENTRAL_MERIDIAN: f64 = -93.0;
+/// Second eccentricity squared of the WGS84 ellipsoid.
+const WGS84_E2_PRIME: f64 = 0.006_739_496_742;
+/// UTM scale factor on the central meridian.
+const UTM_K0: f64 = 0.9996;
+
+/// North an azimuth is measured from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NorthReference {
+    Magnetic,
+    #[default]
+    True,
+    /// UTM 15N grid north.
+    Grid,
+}
+
+/// Angles relating the three north references at a bore site.
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+pub struct AzimuthCorrection {
+    /// Magnetic declination in degrees, east positive (true = magnetic + declination).
+    pub declination: f64,
+    /// Grid convergence in degrees, positive when grid north is east of true
+    /// north (true = grid + convergence).
+    pub grid_convergence: f64,
+}
+
+impl AzimuthCorrection {
+    /// Correction for a bore entering at the given WGS84 latitude/longitude,
+    /// with convergence taken from UTM zone 15N.
+    pub fn for_utm15n(declination: f64, entry_latitude: f64, entry_longitude: f64) -> Self {
+        Self {
+            declination,
+            grid_convergence: utm15n_grid_convergence(entry_latitude, entry_longitude),
+        }
+    }
+
+    /// Re-expresses an azimuth measured from `from` north relative to `to`
+    /// north, normalised to 0..360.
+    pub fn convert(&self, azimuth: f64, from: NorthReference, to: NorthReference) -> f64 {
+        let offset = |reference| match reference {
+            NorthReference::Magnetic => self.declination,
+            NorthReference::True => 0.0,
+            NorthReference::Grid => self.grid_convergence,
+        };
+        let converted = (azimuth + offset(from) - offset(to)).rem_euclid(360.0);
+        // rem_euclid can round a tiny negative up to exactly 360.
+        if converted >= 360.0 {
+            0.0
+        } else {
+            converted
+        }
+    }
+
+    pub fn convert_surveys(
+        &self,
+        surveys: &[SurveyPoint],
+        from: NorthReference,
+        to: NorthReference,
+    ) -> Vec<SurveyPoint> {
+        surveys
+            .iter()
+            .map(|s| SurveyPoint {
+                azimuth: self.convert(s.azimuth, from, to),
+                ..s.clone()
+            })
+            .collect()
+    }
+}
+
+/// Grid convergence (degrees) of UTM zone 15N at a WGS84 latitude/longitude.
+///
+/// Uses the transverse Mercator series to the cubic term, which is accurate
+/// to well under a second of arc within the zone.
+pub fn utm15n_grid_convergence(latitude: f64, longitude: f64) -> f64 {
+    let phi = latitude.to_radians();
+    let d_lambda = (longitude - UTM15_CENTRAL_MERIDIAN).to_radians();
+    let eta2 = WGS84_E2_PRIME * phi.cos().powi(2);
+    let gamma = d_lambda
+        * phi.sin()
+        * (1.0
+            + d_lambda.powi(2) * phi.cos().powi(2) / 3.0 * (1.0 + 3.0 * eta2 + 2.0 * eta2 * eta2));
+    gamma.to_degrees()
+}
+
+/// Point scale factor (grid distance over ellipsoid distance) of UTM zone
+/// 15N at a WGS84 latitude/longitude, from the transverse Mercator series
+/// to the fourth-order term (Snyder, Map Projections, eq. 8-11).
+pub fn utm15n_scale_factor(latitude: f64, longitude: f64) -> f64 {
+    let phi = latitude.to_radians();
+    let a = (longitude - UTM15_CENTRAL_MERIDIAN).to_radians() * phi.cos();
+    let t = phi.tan().powi(2);
+    let c = WGS84_E2_PRIME * phi.cos().powi(2);
+    UTM_K0
+        * (1.0
+            + (1.0 + c) * a * a / 2.0
+            + (5.0 - 4.0 * t + 42.0 * c + 13.0 * c * c - 28.0 * WGS84_E2_PRIME) * a.powi(4) / 24.0)
+}
+
+/// Calculates a path from azimuths measured against `from` north, with north
+/// and east in the path aligned to `to` north.
+///
+/// Stations are validated as given, before any correction wraps their
+/// azimuths into 0..360.
+pub fn calculate_path_in(
+    surveys: &[SurveyPoint],
+    method: SurveyMethod,
+    correction: &AzimuthCorrection,
+    from: NorthReference,
+    to: NorthReference,
+) -> Result<Vec<Coordinate3D>, SurveyError> {
+    crate::validate_stations(surveys)?;
+    calculate_path_with(&correction.convert_surveys(surveys, from, to), method)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_utm15n_convergence() {
+        assert!(utm15n_grid_convergence(46.0, -93.0).abs() < 1e-12);
+        // Minneapolis, just west of the central meridian.
+        assert!((utm15n_grid_convergence(44.9778, -93.265) + 0.18731).abs() < 1e-4);
+        // 3 degrees east at 45N; spherical formula gives 2.1223.
+        assert!((utm15n_grid_convergence(45.0, -90.0) - 2.1223).abs() < 1e-3);
+    }
+
+    #[test]
+    fn test_utm15n_scale_factor() {
+        assert_eq!(utm15n_scale_factor(46.0, -93.0), 0.9996);
+        // Zone edge at 45N; k0 * (1 + (3 deg * cos 45)^2 / 2) to first order.
+        assert!((utm15n_scale_factor(45.0, -90.0) - 1.000_286).abs() < 2e-6);
+    }
+
+    #[test]
+    fn test_convert_between_references() {
+        let correction = AzimuthCorrection {
+            declination: -1.5,
+            grid_convergence: 0.8,
+        };
+        use NorthReference::*;
+        assert!((correction.convert(90.0, Magnetic, True) - 88.5).abs() < 1e-9);
+        assert!((correction.convert(90.0, Magnetic, Grid) - 87.7).abs() < 1e-9);
+        assert!((correction.convert(0.5, Grid, Magnetic) - 2.8).abs() < 1e-9);
+        assert!((correction.convert(1.0, Magnetic, True) - 359.5).abs() < 1e-9);
+        for from in [Magnetic, True, Grid] {
+            for to in [Magnetic, True, Grid] {
+                let there = correction.convert(123.4, from, to);
+                assert!((correction.convert(there, to, from) - 123.4).abs() < 1e-9);
+            }
+        }
+    }
+
+    #[test]
+    fn test_path_in_true_north() {
+        // Magnetic due north with 10 degrees east declination heads 010 true.
+        let surveys = [
+            SurveyPoint {
+                measured_depth: 0.0,
+                inclination: 0.0,
+                azimuth: 0.0,
+            },
+            SurveyPoint {
+                measured_depth: 100.0,
+                inclination: 0.0,
+                azimuth: 0.0,
+            },
+        ];
+        let correction = AzimuthCorrection {
+            declination: 10.0,
+            grid_convergence: 0.0,
+        };
+        let path = calculate_path_in(
+            &surveys,
+            SurveyMethod::MinimumCurvature,
+            &correction,
+            NorthReference::Magnetic,
+            NorthReference::True,
+        )
+        .unwrap();
+        assert!((path[1].north - 100.0 * 10f64.to_radians().cos()).abs() < 1e-9);
+        assert!((path[1].east - 100.0 * 10f64.to_radians().sin()).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_path_in_rejects_raw_azimuth_out_of_range() {
+        let correction = AzimuthCorrection {
+            declination: 10.0,
+            grid_convergence: 0.8,
+        };
+        use NorthReference::*;
+        for azimuth in [-1.0, 720.0] {
+            let surveys = [
+                SurveyPoint {
+                    measured_depth: 0.0,
+                    inclination: 0.0,
+                    azimuth: 0.0,
+                },
+                SurveyPoint {
+                    measured_depth: 100.0,
+                    inclination: 0.0,
+                    azimuth,
+                },
+            ];
+            for from in [Magnetic, True, Grid] {
+                for to in [Magnetic, True, Grid] {
+                    assert!(matches!(
+                        calculate_path_in(
+                            &surveys,
+                            SurveyMethod::MinimumCurvature,
+                            &correction,
+                            from,
+                            to
+                        ),
+                        Err(SurveyError::AzimuthOutOfRange { index: 1, .. })
+                    ));
+                }
+            }
+        }
+    }
+}
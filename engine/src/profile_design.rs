@@ -0,0 +1,345 @@
+use crate::interpolate::PathStation;
+use crate::Coordinate3D;
+use serde::Serialize;
+use thiserror::Error;
+
+#[derive(Error, Debug, Clone, PartialEq)]
+pub enum DesignError {
+    #[error("{field} must be a positive finite number")]
+    NotPositive { field: &'static str },
+    #[error("{field} of {angle} degrees must be between 0 and 90")]
+    AngleOutOfRange { field: &'static str, angle: f64 },
+    #[error("entry and exit are at the same surface location")]
+    CoincidentEndpoints,
+    #[error(
+        "entry and exit are {available:.1} ft apart but the curves and tangents need {required:.1} ft"
+    )]
+    TooShort { required: f64, available: f64 },
+}
+
+/// Inputs for a tangent–curve–horizontal–curve–tangent profile.
+///
+/// Points use the bore path frame (north/east/TVD in ft); `exit.tvd` is
+/// negative when the exit pit is higher than the entry.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProfileConstraints {
+    pub entry: Coordinate3D,
+    pub exit: Coordinate3D,
+    /// Entry angle below horizontal in degrees.
+    pub entry_angle: f64,
+    /// Exit angle above horizontal in degrees.
+    pub exit_angle: f64,
+    /// Minimum bend radius in ft; both curves are drilled at this radius.
+    pub bend_radius: f64,
+    /// Required cover over the horizontal, in ft below the lower of the
+    /// entry and exit.
+    pub depth_of_cover: f64,
+    pub rod_length_ft: f64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SectionKind {
+    EntryTangent,
+    EntryCurve,
+    Horizontal,
+    ExitCurve,
+    ExitTangent,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProfileSection {
+    pub kind: SectionKind,
+    pub start_md: f64,
+    pub end_md: f64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DesignedProfile {
+    /// TVD of the horizontal section. Deeper than the required cover when
+    /// the bend radius can't level out any shallower.
+    pub horizontal_tvd: f64,
+    pub total_md: f64,
+    pub sections: Vec<ProfileSection>,
+    /// Planned position and attitude at every rod joint, from the entry
+    /// (rod 0) to the exit, which may fall part-way through the last rod.
+    pub stations: Vec<PathStation>,
+}
+
+fn require_positive(value: f64, field: &'static str) -> Result<f64, DesignError> {
+    if value.is_finite() && value > 0.0 {
+        Ok(value)
+    } else {
+        Err(DesignError::NotPositive { field })
+    }
+}
+
+fn require_angle(angle: f64, field: &'static str) -> Result<f64, DesignError> {
+    if angle > 0.0 && angle < 90.0 {
+        Ok(angle.to_radians())
+    } else {
+        Err(DesignError::AngleOutOfRange { field, angle })
+    }
+}
+
+/// Designs the profile and lays out rod-joint stations along it.
+///
+/// The horizontal sits at the required cover unless a curve at the minimum
+/// radius would overshoot it, in which case it is lowered until the entry
+/// or exit tangent shrinks to nothing. Fails when the curves and tangents
+/// don't fit between the entry and exit.
+pub fn design_profile(constraints: &ProfileConstraints) -> Result<DesignedProfile, DesignError> {
+    let c = constraints;
+    let radius = require_positive(c.bend_radius, "bend_radius")?;
+    let rod = require_positive(c.rod_length_ft, "rod_length_ft")?;
+    if !(c.depth_of_cover.is_finite() && c.depth_of_cover >= 0.0) {
+        return Err(DesignError::NotPositive {
+            field: "depth_of_cover",
+        });
+    }
+    let a1 = require_angle(c.entry_angle, "entry_angle")?;
+    let a2 = require_angle(c.exit_angle, "exit_angle")?;
+
+    let (dn, de) = (c.exit.north - c.entry.north, c.exit.east - c.entry.east);
+    let available = dn.hypot(de);
+    if available < 1e-9 {
+        return Err(DesignError::CoincidentEndpoints);
+    }
+    let (bearing_n, bearing_e) = (dn / available, de / available);
+
+    let curve_drop = |a: f64| radius * (1.0 - a.cos());
+    let horizontal_tvd = (c.entry.tvd.max(c.exit.tvd) + c.depth_of_cover)
+        .max(c.entry.tvd + curve_drop(a1))
+        .max(c.exit.tvd + curve_drop(a2));
+    let entry_tangent = (horizontal_tvd - c.entry.tvd - curve_drop(a1)) / a1.sin();
+    let exit_tangent = (horizontal_tvd - c.exit.tvd - curve_drop(a2)) / a2.sin();
+    let required =
+        entry_tangent * a1.cos() + radius * a1.sin() + exit_tangent * a2.cos() + radius * a2.sin();
+    if required > available {
+        return Err(DesignError::TooShort {
+            required,
+            available,
+        });
+    }
+
+    let lengths = [
+        (SectionKind::EntryTangent, entry_tangent),
+        (SectionKind::EntryCurve, radius * a1),
+        (SectionKind::Horizontal, available - required),
+        (SectionKind::ExitCurve, radius * a2),
+        (SectionKind::ExitTangent, exit_tangent),
+    ];
+    let mut sections = Vec::with_capacity(lengths.len());
+    let mut md = 0.0;
+    for (kind, length) in lengths {
+        sections.push(ProfileSection {
+            kind,
+            start_md: md,
+            end_md: md + length,
+        });
+        md += length;
+    }
+    let total_md = md;
+
+    // Horizontal offset along the bearing and TVD from the entry, with the
+    // pitch, at an MD.
+    let section_at = |md: f64| -> (f64, f64, f64) {
+        let [t1, k1, h, k2, _] = [0, 1, 2, 3, 4].map(|i| &sections[i]);
+        if md <= t1.end_md {
+            (md * a1.cos(), md * a1.sin(), a1)
+        } else if md <= k1.end_md {
+            let pitch = a1 - (md - k1.start_md) / radius;
+            (
+                entry_tangent * a1.cos() + radius * (a1.sin() - pitch.sin()),
+                entry_tangent * a1.sin() + radius * (pitch.cos() - a1.cos()),
+                pitch,
+            )
+        } else {
+            let s = entry_tangent * a1.cos() + radius * a1.sin();
+            let z = horizontal_tvd - c.entry.tvd;
+            if md <= h.end_md {
+                (s + md - h.start_md, z, 0.0)
+            } else if md <= k2.end_md {
+                let turned = (md - k2.start_md) / radius;
+                (
+                    s + h.end_md - h.start_md + radius * turned.sin(),
+                    z - radius * (1.0 - turned.cos()),
+                    -turned,
+                )
+            } else {
+                let along = md - k2.end_md;
+                (
+                    s + h.end_md - h.start_md + radius * a2.sin() + along * a2.cos(),
+                    z - curve_drop(a2) - along * a2.sin(),
+                    -a2,
+                )
+            }
+        }
+    };
+
+    let azimuth = bearing_e.atan2(bearing_n).to_degrees().rem_euclid(360.0);
+    let joints = (total_md / rod).floor() as usize;
+    let mut mds: Vec<f64> = (0..=joints).map(|i| i as f64 * rod).collect();
+    if total_md - joints as f64 * rod > 1e-6 {
+        mds.push(total_md);
+    }
+    let stations = mds
+        .into_iter()
+        .map(|md| {
+            let (along, down, pitch) = section_at(md);
+            PathStation {
+                measured_depth: md,
+                north: c.entry.north + along * bearing_n,
+                east: c.entry.east + along * bearing_e,
+                tvd: c.entry.tvd + down,
+                pitch: pitch.to_degrees(),
+                azimuth,
+            }
+        })
+        .collect();
+
+    Ok(DesignedProfile {
+        horizontal_tvd,
+        total_md,
+        sections,
+        stations,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{calculate_path, SurveyPoint};
+
+    fn constraints() -> ProfileConstraints {
+        ProfileConstraints {
+            entry: Coordinate3D {
+                north: 0.0,
+                east: 0.0,
+                tvd: 0.0,
+            },
+            exit: Coordinate3D {
+                north: 0.0,
+                east: 800.0,
+                tvd: -4.0,
+            },
+            entry_angle: 12.0,
+            exit_angle: 10.0,
+            bend_radius: 400.0,
+            depth_of_cover: 25.0,
+            rod_length_ft: 10.0,
+        }
+    }
+
+    #[test]
+    fn test_design_reaches_exit() {
+        let profile = design_profile(&constraints()).unwrap();
+        assert_eq!(profile.horizontal_tvd, 25.0);
+        assert_eq!(
+            profile.stations.len(),
+            (profile.total_md / 10.0).ceil() as usize + 1
+        );
+        assert_eq!(profile.stations[3].measured_depth, 30.0);
+        assert!((profile.stations[3].pitch - 12.0).abs() < 1e-9);
+
+        let exit = profile.stations.last().unwrap();
+        assert!((exit.measured_depth - profile.total_md).abs() < 1e-9);
+        assert!((exit.east - 800.0).abs() < 1e-6);
+        assert!(exit.north.abs() < 1e-9);
+        assert!((exit.tvd + 4.0).abs() < 1e-6);
+        assert!((exit.pitch + 10.0).abs() < 1e-9);
+        assert!((exit.azimuth - 90.0).abs() < 1e-9);
+
+        let horizontal = &profile.sections[2];
+        assert_eq!(horizontal.kind, SectionKind::Horizontal);
+        let level = profile
+            .stations
+            .iter()
+            .find(|s| s.measured_depth > horizontal.start_md)
+            .unwrap();
+        assert_eq!(level.pitch, 0.0);
+        assert!((level.tvd - 25.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_joints_match_minimum_curvature() {
+        // Joints on the same arc or tangent survey back to the same points.
+        let profile = design_profile(&constraints()).unwrap();
+        let curve = &profile.sections[1];
+        let joints: Vec<_> = profile
+            .stations
+            .iter()
+            .filter(|s| s.measured_depth >= curve.start_md && s.measured_depth <= curve.end_md)
+            .collect();
+        let surveys: Vec<_> = joints
+            .iter()
+            .map(|s| SurveyPoint {
+                measured_depth: s.measured_depth,
+                inclination: s.pitch,
+                azimuth: s.azimuth,
+            })
+            .collect();
+        let path = calculate_path(&surveys).unwrap();
+        let (first, last) = (joints[0], joints[joints.len() - 1]);
+        let end = path.last().unwrap();
+        assert!((end.east - (last.east - first.east)).abs() < 1e-6);
+        assert!((end.tvd - (last.tvd - first.tvd)).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_tight_radius_deepens_horizontal() {
+        let profile = design_profile(&ProfileConstraints {
+            bend_radius: 2000.0,
+            exit: Coordinate3D {
+                north: 0.0,
+                east: 1500.0,
+                tvd: 0.0,
+            },
+            ..constraints()
+        })
+        .unwrap();
+        let drop = 2000.0 * (1.0 - 12f64.to_radians().cos());
+        assert!((profile.horizontal_tvd - drop).abs() < 1e-9);
+        assert_eq!(profile.sections[0].end_md, 0.0);
+        assert!(profile.sections[4].end_md > profile.sections[4].start_md);
+    }
+
+    #[test]
+    fn test_unmeetable_constraints() {
+        let short = ProfileConstraints {
+            exit: Coordinate3D {
+                north: 0.0,
+                east: 200.0,
+                tvd: 0.0,
+            },
+            ..constraints()
+        };
+        assert!(matches!(
+            design_profile(&short),
+            Err(DesignError::TooShort { available, .. }) if available == 200.0
+        ));
+        let flat = ProfileConstraints {
+            entry_angle: 0.0,
+            ..constraints()
+        };
+        assert!(matches!(
+            design_profile(&flat),
+            Err(DesignError::AngleOutOfRange {
+                field: "entry_angle",
+                ..
+            })
+        ));
+        let no_rods = ProfileConstraints {
+            rod_length_ft: 0.0,
+            ..constraints()
+        };
+        assert_eq!(
+            design_profile(&no_rods),
+            Err(DesignError::NotPositive {
+                field: "rod_length_ft"
+            })
+        );
+    }
+}
@@ -0,0 +1,308 @@
+use crate::mnemonics::Channel;
+use crate::timestamp::{parse_timestamp, NaiveTimes};
+use crate::witsml_parser::TelemetryData;
+use chrono::{DateTime, Duration, SecondsFormat};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// How the samples falling in one bucket are combined.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Aggregation {
+    Mean,
+    Max,
+    Last,
+}
+
+impl Aggregation {
+    fn apply(self, values: impl Iterator<Item = Option<f64>>) -> Option<f64> {
+        let mut values = values.flatten();
+        let first = values.next()?;
+        Some(match self {
+            Aggregation::Mean => {
+                let (sum, count) = values.fold((first, 1), |(s, n), v| (s + v, n + 1));
+                sum / count as f64
+            }
+            Aggregation::Max => values.fold(first, f64::max),
+            Aggregation::Last => values.last().unwrap_or(first),
+        })
+    }
+}
+
+/// Aggregation per channel. `depth` only applies to time-step resampling;
+/// depth-step buckets are labelled with their start depth.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChannelAggregation {
+    pub depth: Aggregation,
+    pub torque: Aggregation,
+    pub thrust: Aggregation,
+    pub mud_flow: Aggregation,
+    pub bit_rpm: Aggregation,
+}
+
+impl Default for ChannelAggregation {
+    fn default() -> Self {
+        Self {
+            depth: Aggregation::Last,
+            torque: Aggregation::Mean,
+            thrust: Aggregation::Mean,
+            mud_flow: Aggregation::Mean,
+            bit_rpm: Aggregation::Mean,
+        }
+    }
+}
+
+impl ChannelAggregation {
+    fn combine(&self, timestamp: String, depth: f64, rows: &[&TelemetryData]) -> TelemetryData {
+        TelemetryData {
+            timestamp,
+            depth,
+            torque: self.torque.apply(rows.iter().map(|r| r.torque)),
+            thrust: self.thrust.apply(rows.iter().map(|r| r.thrust)),
+            mud_flow: self.mud_flow.apply(rows.iter().map(|r| r.mud_flow)),
+            bit_rpm: self.bit_rpm.apply(rows.iter().map(|r| r.bit_rpm)),
+        }
+    }
+}
+
+/// Buckets rows into fixed time steps aligned to multiples of `step` since
+/// the Unix epoch, so logs resampled with the same step line up.
+///
+/// Each output row is stamped with its bucket start in UTC, with plain
+/// date-times placed by `naive_times`. Rows without a parseable timestamp
+/// are dropped, empty buckets are not filled, and a non-positive step
+/// yields no rows.
+pub fn resample_by_time(
+    rows: &[TelemetryData],
+    step: Duration,
+    aggregation: &ChannelAggregation,
+    naive_times: NaiveTimes,
+) -> Vec<TelemetryData> {
+    let step_ms = step.num_milliseconds();
+    if step_ms <= 0 {
+        return Vec::new();
+    }
+    let mut buckets: BTreeMap<i64, Vec<&TelemetryData>> = BTreeMap::new();
+    for row in rows {
+        if let Some(t) = parse_timestamp(&row.timestamp, naive_times) {
+            let bucket = t.timestamp_millis().div_euclid(step_ms);
+            buckets.entry(bucket).or_default().push(row);
+        }
+    }
+    buckets
+        .into_iter()
+        .filter_map(|(bucket, rows)| {
+            let start = DateTime::from_timestamp_millis(bucket * step_ms)?;
+            let depth = aggregation
+                .depth
+                .apply(rows.iter().map(|r| Some(r.depth)))?;
+            Some(aggregation.combine(
+                start.to_rfc3339_opts(SecondsFormat::AutoSi, true),
+                depth,
+                &rows,
+            ))
+        })
+        .collect()
+}
+
+/// Buckets rows into fixed depth steps aligned to multiples of `step_ft`.
+///
+/// Each output row carries its bucket start depth and the timestamp of the
+/// last sample in the bucket. Empty buckets are not filled and a
+/// non-positive step yields no rows.
+pub fn resample_by_depth(
+    rows: &[TelemetryData],
+    step_ft: f64,
+    aggregation: &ChannelAggregation,
+) -> Vec<TelemetryData> {
+    if step_ft <= 0.0 || !step_ft.is_finite() {
+        return Vec::new();
+    }
+    let mut buckets: BTreeMap<i64, Vec<&TelemetryData>> = BTreeMap::new();
+    for row in rows.iter().filter(|r| r.depth.is_finite()) {
+        let bucket = (row.depth / step_ft).floor() as i64;
+        buckets.entry(bucket).or_default().push(row);
+    }
+    buckets
+        .into_iter()
+        .map(|(bucket, rows)| {
+            let timestamp = rows.last().map(|r| r.timestamp.clone()).unwrap_or_default();
+            aggregation.combine(timestamp, bucket as f64 * step_ft, &rows)
+        })
+        .collect()
+}
+
+fn channel_value(row: &TelemetryData, channel: Channel) -> Option<f64> {
+    match channel {
+        Channel::Timestamp => None,
+        Channel::Depth => Some(row.depth),
+        Channel::Torque => row.torque,
+        Channel::Thrust => row.thrust,
+        Channel::MudFlow => row.mud_flow,
+        Channel::BitRpm => row.bit_rpm,
+    }
+}
+
+/// Picks `target` original samples that keep the visual shape of `channel`,
+/// using Largest-Triangle-Three-Buckets.
+///
+/// The x axis is elapsed time when every row has a timestamp and depth
+/// otherwise. First and last rows are always kept, and peaks and troughs
+/// survive because each bucket keeps the point spanning the largest
+/// triangle with its neighbours. Samples missing `channel` are only picked
+/// when a whole bucket is missing it.
+pub fn downsample_lttb(
+    rows: &[TelemetryData],
+    target: usize,
+    channel: Channel,
+) -> Vec<TelemetryData> {
+    if target >= rows.len() || target < 3 {
+        return rows.to_vec();
+    }
+    // Only spacing matters here, so plain date-times can be read as UTC.
+    let times: Option<Vec<f64>> = rows
+        .iter()
+        .map(|r| {
+            parse_timestamp(&r.timestamp, NaiveTimes::Utc)
+                .map(|t| t.timestamp_millis() as f64 / 1000.0)
+        })
+        .collect();
+    let xs = times.unwrap_or_else(|| rows.iter().map(|r| r.depth).collect());
+    let ys: Vec<Option<f64>> = rows.iter().map(|r| channel_value(r, channel)).collect();
+
+    let last = rows.len() - 1;
+    let bucket_size = (rows.len() - 2) as f64 / (target - 2) as f64;
+    let bucket_bounds = |b: usize| {
+        let start = (b as f64 * bucket_size) as usize + 1;
+        let end = (((b + 1) as f64 * bucket_size) as usize + 1).min(last);
+        start..end
+    };
+
+    let mut picked = vec![0];
+    let mut anchor = 0;
+    for b in 0..target - 2 {
+        // Average of the next bucket, or the last point for the final bucket.
+        let next = if b + 1 < target - 2 {
+            bucket_bounds(b + 1)
+        } else {
+            last..last + 1
+        };
+        let known: Vec<usize> = next.clone().filter(|&i| ys[i].is_some()).collect();
+        let (avg_x, avg_y) = if known.is_empty() {
+            (xs[next.start], ys[anchor].unwrap_or(0.0))
+        } else {
+            let n = known.len() as f64;
+            (
+                known.iter().map(|&i| xs[i]).sum::<f64>() / n,
+                known.iter().filter_map(|&i| ys[i]).sum::<f64>() / n,
+            )
+        };
+
+        let (ax, ay) = (xs[anchor], ys[anchor].unwrap_or(avg_y));
+        let range = bucket_bounds(b);
+        let choice = range
+            .clone()
+            .filter_map(|i| {
+                let y = ys[i]?;
+                let area = ((ax - avg_x) * (y - ay) - (ax - xs[i]) * (avg_y - ay)).abs();
+                Some((i, area))
+            })
+            .max_by(|a, b| a.1.total_cmp(&b.1))
+            .map_or(range.start, |(i, _)| i);
+        picked.push(choice);
+        anchor = choice;
+    }
+    picked.push(last);
+    picked.into_iter().map(|i| rows[i].clone()).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn row(second: i64, depth: f64, torque: Option<f64>) -> TelemetryData {
+        let t = DateTime::from_timestamp(1_714_557_600 + second, 0).unwrap();
+        TelemetryData {
+            timestamp: t.to_rfc3339_opts(SecondsFormat::AutoSi, true),
+            depth,
+            torque,
+            thrust: Some(second as f64),
+            mud_flow: None,
+            bit_rpm: Some(60.0),
+        }
+    }
+
+    #[test]
+    fn test_resample_by_time() {
+        let rows: Vec<_> = (0..25)
+            .map(|s| row(s, s as f64 * 0.1, Some(s as f64)))
+            .collect();
+        let aggregation = ChannelAggregation {
+            thrust: Aggregation::Max,
+            ..Default::default()
+        };
+        let out = resample_by_time(&rows, Duration::seconds(10), &aggregation, NaiveTimes::Utc);
+        assert_eq!(out.len(), 3);
+        assert_eq!(out[0].timestamp, "2024-05-01T10:00:00Z");
+        assert_eq!(out[1].timestamp, "2024-05-01T10:00:10Z");
+        assert_eq!(out[0].torque, Some(4.5));
+        assert_eq!(out[0].thrust, Some(9.0));
+        assert!((out[0].depth - 0.9).abs() < 1e-9);
+        assert_eq!(out[2].mud_flow, None);
+        assert!(
+            resample_by_time(&rows, Duration::zero(), &aggregation, NaiveTimes::Utc).is_empty()
+        );
+
+        // Plain rig-clock times on UTC-5 are bucketed and labelled in UTC.
+        let local: Vec<_> = rows
+            .iter()
+            .map(|r| TelemetryData {
+                timestamp: r.timestamp.trim_end_matches('Z').replacen('T', " ", 1),
+                ..r.clone()
+            })
+            .collect();
+        let rig = NaiveTimes::Offset(chrono::FixedOffset::west_opt(5 * 3600).unwrap());
+        let out = resample_by_time(&local, Duration::seconds(10), &aggregation, rig);
+        assert_eq!(out.len(), 3);
+        assert_eq!(out[0].timestamp, "2024-05-01T15:00:00Z");
+        assert_eq!(out[0].torque, Some(4.5));
+    }
+
+    #[test]
+    fn test_resample_by_depth() {
+        let rows: Vec<_> = (0..30)
+            .map(|s| row(s, 100.0 + s as f64 * 0.25, Some(s as f64)))
+            .collect();
+        let aggregation = ChannelAggregation {
+            torque: Aggregation::Last,
+            ..Default::default()
+        };
+        let out = resample_by_depth(&rows, 2.0, &aggregation);
+        assert_eq!(
+            out.iter().map(|r| r.depth).collect::<Vec<_>>(),
+            vec![100.0, 102.0, 104.0, 106.0]
+        );
+        assert_eq!(out[0].torque, Some(7.0));
+        assert_eq!(out[0].timestamp, rows[7].timestamp);
+        assert_eq!(out[3].torque, Some(29.0));
+    }
+
+    #[test]
+    fn test_lttb_keeps_extremes() {
+        let mut rows: Vec<_> = (0..1000)
+            .map(|s| row(s, s as f64, Some((s % 7) as f64)))
+            .collect();
+        rows[400].torque = Some(500.0);
+        rows[700].torque = Some(-500.0);
+        rows[100].torque = None;
+
+        let out = downsample_lttb(&rows, 50, Channel::Torque);
+        assert_eq!(out.len(), 50);
+        assert_eq!(out.first(), rows.first());
+        assert_eq!(out.last(), rows.last());
+        assert!(out.iter().any(|r| r.torque == Some(500.0)));
+        assert!(out.iter().any(|r| r.torque == Some(-500.0)));
+        assert!(out.windows(2).all(|w| w[0].depth < w[1].depth));
+        assert_eq!(downsample_lttb(&rows[..10], 50, Channel::Torque).len(), 10);
+    }
+}
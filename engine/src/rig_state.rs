@@ -0,0 +1,308 @@
+use crate::timestamp::{parse_timestamp, NaiveTimes};
+use crate::witsml_parser::TelemetryData;
+use serde::Serialize;
+
+/// What the rig was doing during a sample.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RigState {
+    /// Advancing at the deepest point reached so far.
+    Drilling,
+    /// Rotating in place without advancing.
+    RotatingOffBottom,
+    /// Short stop with no rotation or flow between moves, e.g. adding a rod.
+    Connection,
+    /// Pulling out against a high pull force (product pullback).
+    PullingBack,
+    /// Moving through already-drilled hole while rotating and pumping.
+    Reaming,
+    /// Moving rods in or out without rotation or flow.
+    Tripping,
+    Idle,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct RigStateConfig {
+    /// Depth rate (ft/min) below which the string counts as stationary.
+    pub min_move_rate_ft_per_min: f64,
+    /// Within this distance (ft) of the deepest point the bit is on bottom.
+    pub on_bottom_tolerance_ft: f64,
+    pub rotating_rpm: f64,
+    pub pumping_gpm: f64,
+    /// Pull force (lbf) that marks pulling out as pullback rather than tripping.
+    pub pullback_force_lbf: f64,
+    /// Longest stop (s) still counted as a connection; longer stops are idle.
+    pub max_connection_seconds: f64,
+    /// Spacing (s) assumed between samples without usable timestamps.
+    pub sample_interval_seconds: f64,
+}
+
+impl Default for RigStateConfig {
+    fn default() -> Self {
+        Self {
+            min_move_rate_ft_per_min: 0.5,
+            on_bottom_tolerance_ft: 0.5,
+            rotating_rpm: 5.0,
+            pumping_gpm: 5.0,
+            pullback_force_lbf: 5_000.0,
+            max_connection_seconds: 300.0,
+            sample_interval_seconds: 1.0,
+        }
+    }
+}
+
+/// A run of consecutive samples in the same state.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct StateInterval {
+    pub state: RigState,
+    pub start_row: usize,
+    pub end_row: usize,
+    pub start_depth: f64,
+    pub end_depth: f64,
+    pub duration_seconds: f64,
+}
+
+/// Time breakdown for one bore.
+#[derive(Debug, Clone, PartialEq, Default, Serialize)]
+pub struct RigActivitySummary {
+    pub drilling_seconds: f64,
+    pub reaming_seconds: f64,
+    pub pullback_seconds: f64,
+    pub tripping_seconds: f64,
+    pub rotating_off_bottom_seconds: f64,
+    pub connection_seconds: f64,
+    pub connection_count: usize,
+    /// Idle time, i.e. stops too long to be connections.
+    pub non_productive_seconds: f64,
+    pub total_seconds: f64,
+}
+
+impl RigActivitySummary {
+    pub fn average_connection_seconds(&self) -> Option<f64> {
+        (self.connection_count > 0).then(|| self.connection_seconds / self.connection_count as f64)
+    }
+}
+
+/// Seconds since the first sample, from timestamps where they parse and the
+/// nominal sample interval otherwise.
+fn elapsed_seconds(rows: &[TelemetryData], config: &RigStateConfig) -> Vec<f64> {
+    let parsed: Vec<_> = rows
+        .iter()
+        // Any fixed offset gives the same elapsed times, so plain
+        // date-times can be read as UTC.
+        .map(|r| parse_timestamp(&r.timestamp, NaiveTimes::Utc))
+        .collect();
+    if parsed.iter().all(Option::is_some) {
+        if let Some(Some(first)) = parsed.first() {
+            return parsed
+                .iter()
+                .flatten()
+                .map(|t| (*t - *first).num_milliseconds() as f64 / 1000.0)
+                .collect();
+        }
+    }
+    (0..rows.len())
+        .map(|i| i as f64 * config.sample_interval_seconds)
+        .collect()
+}
+
+/// How long each sample lasts: the time until the next one.
+fn sample_durations(times: &[f64], config: &RigStateConfig) -> Vec<f64> {
+    (0..times.len())
+        .map(|i| match times.get(i + 1) {
+            Some(next) => (next - times[i]).max(0.0),
+            None => config.sample_interval_seconds,
+        })
+        .collect()
+}
+
+/// Labels every telemetry sample with a rig state.
+///
+/// Movement comes from the depth rate between neighbouring samples; stops
+/// without rotation or flow are split into connections and idle time by
+/// their length.
+pub fn classify(rows: &[TelemetryData], config: &RigStateConfig) -> Vec<RigState> {
+    let times = elapsed_seconds(rows, config);
+    let mut states = Vec::with_capacity(rows.len());
+    let mut deepest = f64::NEG_INFINITY;
+
+    for (i, row) in rows.iter().enumerate() {
+        let prev = i.saturating_sub(1);
+        let next = (i + 1).min(rows.len() - 1);
+        let dt_min = (times[next] - times[prev]) / 60.0;
+        let rate = if dt_min > 0.0 {
+            (rows[next].depth - rows[prev].depth) / dt_min
+        } else {
+            0.0
+        };
+
+        let rotating = row.bit_rpm.unwrap_or(0.0) > config.rotating_rpm;
+        let pumping = row.mud_flow.unwrap_or(0.0) > config.pumping_gpm;
+        let on_bottom = row.depth >= deepest - config.on_bottom_tolerance_ft;
+        deepest = deepest.max(row.depth);
+
+        let state = if rate > config.min_move_rate_ft_per_min {
+            if on_bottom {
+                RigState::Drilling
+            } else if rotating || pumping {
+                RigState::Reaming
+            } else {
+                RigState::Tripping
+            }
+        } else if rate < -config.min_move_rate_ft_per_min {
+            if row.thrust.unwrap_or(0.0).abs() >= config.pullback_force_lbf {
+                RigState::PullingBack
+            } else if rotating && pumping {
+                RigState::Reaming
+            } else {
+                RigState::Tripping
+            }
+        } else if rotating {
+            RigState::RotatingOffBottom
+        } else {
+            // Stops are split into connections and idle time below.
+            RigState::Idle
+        };
+        states.push(state);
+    }
+
+    let durations = sample_durations(&times, config);
+    let mut start = 0;
+    while start < states.len() {
+        let mut end = start;
+        while end + 1 < states.len() && states[end + 1] == states[start] {
+            end += 1;
+        }
+        let moving_before = start > 0;
+        let moving_after = end + 1 < states.len();
+        let length: f64 = durations[start..=end].iter().sum();
+        if states[start] == RigState::Idle
+            && moving_before
+            && moving_after
+            && length <= config.max_connection_seconds
+        {
+            states[start..=end].fill(RigState::Connection);
+        }
+        start = end + 1;
+    }
+    states
+}
+
+/// Groups classified samples into runs of the same state.
+pub fn state_intervals(
+    rows: &[TelemetryData],
+    states: &[RigState],
+    config: &RigStateConfig,
+) -> Vec<StateInterval> {
+    let durations = sample_durations(&elapsed_seconds(rows, config), config);
+    let mut intervals: Vec<StateInterval> = Vec::new();
+    for (i, (row, state)) in rows.iter().zip(states).enumerate() {
+        match intervals.last_mut() {
+            Some(last) if last.state == *state => {
+                last.end_row = i;
+                last.end_depth = row.depth;
+                last.duration_seconds += durations[i];
+            }
+            _ => intervals.push(StateInterval {
+                state: *state,
+                start_row: i,
+                end_row: i,
+                start_depth: row.depth,
+                end_depth: row.depth,
+                duration_seconds: durations[i],
+            }),
+        }
+    }
+    intervals
+}
+
+/// Classifies a bore's telemetry and totals time per activity, including
+/// non-productive (idle) time and connection time.
+pub fn summarize(rows: &[TelemetryData], config: &RigStateConfig) -> RigActivitySummary {
+    let states = classify(rows, config);
+    let mut summary = RigActivitySummary::default();
+    for interval in state_intervals(rows, &states, config) {
+        let seconds = interval.duration_seconds;
+        summary.total_seconds += seconds;
+        match interval.state {
+            RigState::Drilling => summary.drilling_seconds += seconds,
+            RigState::Reaming => summary.reaming_seconds += seconds,
+            RigState::PullingBack => summary.pullback_seconds += seconds,
+            RigState::Tripping => summary.tripping_seconds += seconds,
+            RigState::RotatingOffBottom => summary.rotating_off_bottom_seconds += seconds,
+            RigState::Connection => {
+                summary.connection_seconds += seconds;
+                summary.connection_count += 1;
+            }
+            RigState::Idle => summary.non_productive_seconds += seconds,
+        }
+    }
+    summary
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn row(t: usize, depth: f64, rpm: f64, flow: f64, thrust: f64) -> TelemetryData {
+        TelemetryData {
+            timestamp: format!("2024-05-01T10:{:02}:{:02}Z", t / 60, t % 60),
+            depth,
+            torque: None,
+            thrust: Some(thrust),
+            mud_flow: Some(flow),
+            bit_rpm: Some(rpm),
+        }
+    }
+
+    /// Drill a rod, stop 60 s for a connection, drill again, then sit idle.
+    fn bore() -> Vec<TelemetryData> {
+        let mut rows = Vec::new();
+        let mut t = 0;
+        for i in 0..30 {
+            rows.push(row(t, i as f64 * 0.33, 60.0, 40.0, 8000.0));
+            t += 1;
+        }
+        let depth = rows.last().unwrap().depth;
+        for _ in 0..60 {
+            rows.push(row(t, depth, 0.0, 0.0, 0.0));
+            t += 1;
+        }
+        for i in 1..=30 {
+            rows.push(row(t, depth + i as f64 * 0.33, 60.0, 40.0, 8000.0));
+            t += 1;
+        }
+        rows
+    }
+
+    #[test]
+    fn test_drilling_and_connection() {
+        let rows = bore();
+        let config = RigStateConfig::default();
+        let states = classify(&rows, &config);
+        assert_eq!(states[10], RigState::Drilling);
+        assert_eq!(states[60], RigState::Connection);
+        assert_eq!(states[100], RigState::Drilling);
+
+        let summary = summarize(&rows, &config);
+        assert_eq!(summary.connection_count, 1);
+        assert!((summary.connection_seconds - 58.0).abs() <= 2.0);
+        assert_eq!(summary.non_productive_seconds, 0.0);
+    }
+
+    #[test]
+    fn test_pullback_reaming_and_idle() {
+        let config = RigStateConfig::default();
+        let mut rows: Vec<TelemetryData> = (0..20)
+            .map(|t| row(t, 500.0 - t as f64 * 0.5, 40.0, 80.0, 20_000.0))
+            .collect();
+        rows.extend((20..40).map(|t| row(t, 490.0 - (t - 20) as f64 * 0.5, 40.0, 80.0, 1_000.0)));
+        rows.extend((40..400).map(|t| row(t, 480.5, 0.0, 0.0, 0.0)));
+
+        let states = classify(&rows, &config);
+        assert_eq!(states[5], RigState::PullingBack);
+        assert_eq!(states[30], RigState::Reaming);
+        assert_eq!(states[200], RigState::Idle);
+        assert!(summarize(&rows, &config).non_productive_seconds > 300.0);
+    }
+}
@@ -0,0 +1,344 @@
+use crate::rig_state::{classify, RigState, RigStateConfig};
+use crate::timestamp::{format_timestamp_like, parse_timestamp, NaiveTimes};
+use crate::witsml_parser::TelemetryData;
+use serde::Serialize;
+use thiserror::Error;
+
+#[derive(Error, Debug, Clone, PartialEq)]
+pub enum RodPassError {
+    #[error("rod length must be a positive finite number of feet, got {0}")]
+    InvalidRodLength(f64),
+}
+
+/// How a telemetry stream is cut into rods.
+#[derive(Debug, Clone, PartialEq)]
+pub enum RodSplit {
+    /// Start a new rod after every connection the rig-state classifier finds.
+    Connections(RigStateConfig),
+    /// Start a new rod every `rod_length_ft` of travel from the first sample.
+    /// Rods are cut at the boundary depths, so each full rod is exactly
+    /// `rod_length_ft` whatever the sample spacing.
+    FixedLength { rod_length_ft: f64 },
+}
+
+impl Default for RodSplit {
+    fn default() -> Self {
+        RodSplit::Connections(RigStateConfig::default())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize)]
+pub struct ChannelStats {
+    pub average: Option<f64>,
+    pub max: Option<f64>,
+}
+
+impl ChannelStats {
+    fn from_values(values: impl Iterator<Item = Option<f64>>) -> Self {
+        let (mut sum, mut count, mut max) = (0.0, 0usize, None::<f64>);
+        for v in values.flatten() {
+            sum += v;
+            count += 1;
+            max = Some(max.map_or(v, |m| m.max(v)));
+        }
+        Self {
+            average: (count > 0).then(|| sum / count as f64),
+            max,
+        }
+    }
+}
+
+/// One rod's worth of telemetry, shaped to pre-fill a `RodPass` record.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RodSummary {
+    /// 1-based rod number within the stream.
+    pub sequence: usize,
+    pub started_at: Option<String>,
+    pub completed_at: Option<String>,
+    pub start_depth: f64,
+    pub end_depth: f64,
+    /// Distance travelled along the hole, regardless of direction.
+    pub linear_feet: f64,
+    pub torque: ChannelStats,
+    pub thrust: ChannelStats,
+    pub mud_flow: ChannelStats,
+    pub bit_rpm: ChannelStats,
+    pub sample_count: usize,
+}
+
+impl RodSummary {
+    fn from_rows(sequence: usize, rows: &[&TelemetryData]) -> Self {
+        let first = rows[0];
+        let last = rows[rows.len() - 1];
+        let time = |r: &TelemetryData| (!r.timestamp.is_empty()).then(|| r.timestamp.clone());
+        Self {
+            sequence,
+            started_at: time(first),
+            completed_at: time(last),
+            start_depth: first.depth,
+            end_depth: last.depth,
+            linear_feet: (last.depth - first.depth).abs(),
+            torque: ChannelStats::from_values(rows.iter().map(|r| r.torque)),
+            thrust: ChannelStats::from_values(rows.iter().map(|r| r.thrust)),
+            mud_flow: ChannelStats::from_values(rows.iter().map(|r| r.mud_flow)),
+            bit_rpm: ChannelStats::from_values(rows.iter().map(|r| r.bit_rpm)),
+            sample_count: rows.len(),
+        }
+    }
+}
+
+/// Splits telemetry into rods and summarises each one.
+///
+/// With [`RodSplit::Connections`] the connection samples themselves are left
+/// out and stretches that cover no footage (e.g. idle time before the first
+/// rod) are dropped. With [`RodSplit::FixedLength`] travel only counts
+/// forward, so small depth wobbles at a boundary stay in the later rod. Rod
+/// start and completion times are interpolated at the boundary depths; a
+/// rod that no sample fell inside (samples further apart than a rod) is
+/// still reported, with no channel stats. A sample landing exactly on the
+/// last boundary completes the final rod rather than starting a new one.
+pub fn aggregate_rods(
+    rows: &[TelemetryData],
+    split: &RodSplit,
+) -> Result<Vec<RodSummary>, RodPassError> {
+    let summaries = match split {
+        RodSplit::Connections(config) => {
+            let states = classify(rows, config);
+            let mut groups: Vec<Vec<&TelemetryData>> = Vec::new();
+            let mut current = Vec::new();
+            for (row, state) in rows.iter().zip(&states) {
+                if *state == RigState::Connection {
+                    if !current.is_empty() {
+                        groups.push(std::mem::take(&mut current));
+                    }
+                } else {
+                    current.push(row);
+                }
+            }
+            if !current.is_empty() {
+                groups.push(current);
+            }
+            groups.iter().map(|g| RodSummary::from_rows(0, g)).collect()
+        }
+        RodSplit::FixedLength { rod_length_ft } => fixed_length_rods(rows, *rod_length_ft)?,
+    };
+
+    Ok(summaries
+        .into_iter()
+        .filter(|r| r.linear_feet > 0.0)
+        .enumerate()
+        .map(|(i, r)| RodSummary {
+            sequence: i + 1,
+            ..r
+        })
+        .collect())
+}
+
+fn fixed_length_rods(
+    rows: &[TelemetryData],
+    rod_length_ft: f64,
+) -> Result<Vec<RodSummary>, RodPassError> {
+    if !(rod_length_ft.is_finite() && rod_length_ft > 0.0) {
+        return Err(RodPassError::InvalidRodLength(rod_length_ft));
+    }
+    let Some(origin) = rows.first().map(|r| r.depth) else {
+        return Ok(Vec::new());
+    };
+    // Furthest travel from the origin so far, at each sample.
+    let mut furthest: Vec<f64> = Vec::with_capacity(rows.len());
+    let mut direction = 1.0;
+    for row in rows {
+        let travel = (row.depth - origin).abs();
+        if furthest.last().is_none_or(|&f| travel > f) {
+            direction = if row.depth < origin { -1.0 } else { 1.0 };
+        }
+        furthest.push(furthest.last().map_or(travel, |&f| f.max(travel)));
+    }
+    let total = furthest[furthest.len() - 1];
+    let count = (total / rod_length_ft).ceil() as usize;
+    if count == 0 {
+        return Ok(Vec::new());
+    }
+
+    let mut groups: Vec<Vec<&TelemetryData>> = vec![Vec::new(); count];
+    for (row, travel) in rows.iter().zip(&furthest) {
+        let rod = ((travel / rod_length_ft).floor() as usize).min(count - 1);
+        groups[rod].push(row);
+    }
+
+    // Time at which the bore first reached `travel`, interpolated between
+    // the samples either side.
+    let time_at = |travel: f64| -> Option<String> {
+        let i = furthest
+            .partition_point(|&f| f < travel)
+            .min(rows.len() - 1);
+        let after = &rows[i].timestamp;
+        let interpolated = i.checked_sub(1).and_then(|before| {
+            // Only the spacing matters, so plain date-times can be read as UTC.
+            let t0 = parse_timestamp(&rows[before].timestamp, NaiveTimes::Utc)?;
+            let t1 = parse_timestamp(after, NaiveTimes::Utc)?;
+            let f = (travel - furthest[before]) / (furthest[i] - furthest[before]);
+            let ms = ((t1 - t0).num_milliseconds() as f64 * f).round() as i64;
+            Some(format_timestamp_like(
+                t0 + chrono::Duration::milliseconds(ms),
+                after,
+            ))
+        });
+        interpolated.or_else(|| (!after.is_empty()).then(|| after.clone()))
+    };
+
+    Ok(groups
+        .iter()
+        .enumerate()
+        .map(|(k, group)| {
+            let start = k as f64 * rod_length_ft;
+            let end = ((k + 1) as f64 * rod_length_ft).min(total);
+            let completed_at = if k + 1 == count {
+                group
+                    .last()
+                    .filter(|r| !r.timestamp.is_empty())
+                    .map(|r| r.timestamp.clone())
+                    .or_else(|| time_at(end))
+            } else {
+                time_at(end)
+            };
+            RodSummary {
+                sequence: 0,
+                started_at: time_at(start),
+                completed_at,
+                start_depth: origin + direction * start,
+                end_depth: origin + direction * end,
+                linear_feet: end - start,
+                torque: ChannelStats::from_values(group.iter().map(|r| r.torque)),
+                thrust: ChannelStats::from_values(group.iter().map(|r| r.thrust)),
+                mud_flow: ChannelStats::from_values(group.iter().map(|r| r.mud_flow)),
+                bit_rpm: ChannelStats::from_values(group.iter().map(|r| r.bit_rpm)),
+                sample_count: group.len(),
+            }
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn row(t: usize, depth: f64, torque: f64, rpm: f64) -> TelemetryData {
+        TelemetryData {
+            timestamp: format!("2024-05-01T10:{:02}:{:02}Z", t / 60, t % 60),
+            depth,
+            torque: Some(torque),
+            thrust: Some(8000.0),
+            mud_flow: Some(40.0),
+            bit_rpm: Some(rpm),
+        }
+    }
+
+    /// Two 10 ft rods drilled at 0.5 ft/s with a 40 s connection between.
+    fn two_rods() -> Vec<TelemetryData> {
+        let mut rows: Vec<_> = (0..=20)
+            .map(|t| row(t, t as f64 * 0.5, 1000.0 + t as f64, 60.0))
+            .collect();
+        rows.extend((21..60).map(|t| TelemetryData {
+            bit_rpm: Some(0.0),
+            mud_flow: Some(0.0),
+            ..row(t, 10.0, 0.0, 0.0)
+        }));
+        rows.extend((60..=80).map(|t| row(t, 10.0 + (t - 60) as f64 * 0.5, 2000.0, 70.0)));
+        rows
+    }
+
+    #[test]
+    fn test_split_on_connections() {
+        let rods = aggregate_rods(&two_rods(), &RodSplit::default()).unwrap();
+        assert_eq!(rods.len(), 2);
+        assert_eq!(rods[0].sequence, 1);
+        assert!((rods[0].linear_feet - 10.0).abs() < 1e-9);
+        assert_eq!(rods[0].started_at.as_deref(), Some("2024-05-01T10:00:00Z"));
+        assert_eq!(rods[0].torque.max, Some(1020.0));
+        assert!((rods[0].torque.average.unwrap() - 1010.0).abs() < 1e-9);
+        assert_eq!(rods[1].bit_rpm.average, Some(70.0));
+        assert_eq!(
+            rods[1].completed_at.as_deref(),
+            Some("2024-05-01T10:01:20Z")
+        );
+    }
+
+    #[test]
+    fn test_split_on_fixed_length() {
+        let split = RodSplit::FixedLength {
+            rod_length_ft: 10.0,
+        };
+        let rows: Vec<_> = (0..=30).map(|t| row(t, t as f64, 1000.0, 60.0)).collect();
+        let rods = aggregate_rods(&rows, &split).unwrap();
+        assert_eq!(rods.len(), 3);
+        assert_eq!(rods[1].start_depth, 10.0);
+        assert_eq!(rods[1].end_depth, 20.0);
+        assert_eq!(rods[1].sample_count, 10);
+        assert!(rods.iter().all(|r| r.linear_feet == 10.0));
+        assert_eq!(rods[2].sample_count, 11);
+        assert_eq!(
+            rods[2].completed_at.as_deref(),
+            Some("2024-05-01T10:00:30Z")
+        );
+
+        // A partial last rod keeps its footage.
+        let rows: Vec<_> = (0..=34).map(|t| row(t, t as f64, 1000.0, 60.0)).collect();
+        let rods = aggregate_rods(&rows, &split).unwrap();
+        assert_eq!(rods.len(), 4);
+        assert_eq!(rods[3].linear_feet, 4.0);
+        assert_eq!(rods.iter().map(|r| r.linear_feet).sum::<f64>(), 34.0);
+    }
+
+    #[test]
+    fn test_fixed_length_cuts_at_boundaries() {
+        let split = RodSplit::FixedLength {
+            rod_length_ft: 10.0,
+        };
+        // A sample every 3 ft and every second.
+        let rows: Vec<_> = (0..=10)
+            .map(|t| row(t, t as f64 * 3.0, 1000.0, 60.0))
+            .collect();
+        let rods = aggregate_rods(&rows, &split).unwrap();
+        assert_eq!(rods.len(), 3);
+        assert!(rods.iter().all(|r| r.linear_feet == 10.0));
+        assert_eq!((rods[1].start_depth, rods[1].end_depth), (10.0, 20.0));
+        assert_eq!(
+            rods.iter().map(|r| r.sample_count).collect::<Vec<_>>(),
+            [4, 3, 4]
+        );
+        assert_eq!(
+            rods[0].completed_at.as_deref(),
+            Some("2024-05-01T10:00:03.333Z")
+        );
+        assert_eq!(rods[1].started_at, rods[0].completed_at);
+
+        // Samples further apart than a rod still give one rod per boundary.
+        let rows: Vec<_> = (0..=2)
+            .map(|t| row(t, t as f64 * 25.0, 1000.0, 60.0))
+            .collect();
+        let rods = aggregate_rods(&rows, &split).unwrap();
+        assert_eq!(rods.len(), 5);
+        assert!(rods.iter().all(|r| r.linear_feet == 10.0));
+        assert_eq!(
+            rods.iter().map(|r| r.sample_count).collect::<Vec<_>>(),
+            [1, 0, 1, 0, 1]
+        );
+        assert_eq!(rods[1].torque.average, None);
+        assert_eq!(
+            rods[2].started_at.as_deref(),
+            Some("2024-05-01T10:00:00.800Z")
+        );
+        assert_eq!(rods[4].end_depth, 50.0);
+    }
+
+    #[test]
+    fn test_rejects_bad_rod_length() {
+        let rows: Vec<_> = (0..=5).map(|t| row(t, t as f64, 1000.0, 60.0)).collect();
+        for rod_length_ft in [0.0, -10.0, f64::NAN, f64::INFINITY] {
+            let result = aggregate_rods(&rows, &RodSplit::FixedLength { rod_length_ft });
+            assert!(matches!(result, Err(RodPassError::InvalidRodLength(_))));
+        }
+    }
+}
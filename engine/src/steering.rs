@@ -0,0 +1,330 @@
+use crate::interpolate::SurveyPath;
+use crate::{calculate_path, SurveyError, SurveyPoint};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+/// Offsets (ft) and angle errors (radians) below which the bore is on plan.
+const ON_PLAN_OFFSET_FT: f64 = 0.05;
+const ON_PLAN_ANGLE: f64 = 1e-4;
+
+#[derive(Error, Debug, Clone, PartialEq)]
+pub enum SteeringError {
+    #[error(transparent)]
+    Survey(#[from] SurveyError),
+    #[error("rod length and build/turn rates must be positive")]
+    InvalidCapability,
+    #[error("cannot rejoin the plan within {max_rods} rods at the rig's build/turn rates")]
+    NoSolution { max_rods: usize },
+}
+
+/// What the rig can steer.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SteeringCapability {
+    pub rod_length_ft: f64,
+    /// Pitch change over a fully steered rod, in degrees.
+    pub build_per_rod_deg: f64,
+    /// Horizontal direction change over a fully steered rod, in degrees.
+    pub turn_per_rod_deg: f64,
+    /// Longest correction to consider.
+    pub max_rods: usize,
+}
+
+impl Default for SteeringCapability {
+    fn default() -> Self {
+        Self {
+            rod_length_ft: 10.0,
+            build_per_rod_deg: 1.0,
+            turn_per_rod_deg: 1.0,
+            max_rods: 40,
+        }
+    }
+}
+
+/// Hold one tool face for a number of rods.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SteeringPhase {
+    /// Tool face in degrees clockwise from high side, looking downhole.
+    pub tool_face: f64,
+    /// Tool face as a clock position to the nearest half hour, e.g. "10:30".
+    pub clock: String,
+    pub rods: usize,
+    /// Share of each rod to push without rotation; the rest is rotated.
+    pub steer_fraction: f64,
+    pub start_md: f64,
+    pub end_md: f64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SteeringAdvice {
+    pub measured_depth: f64,
+    /// Current offset above the plan in ft.
+    pub high: f64,
+    /// Current offset right of the plan in ft.
+    pub right: f64,
+    /// Empty when already on plan.
+    pub phases: Vec<SteeringPhase>,
+    /// Predicted MD at which the bore is back on plan.
+    pub landing_md: f64,
+    /// Planned MD at the landing point.
+    pub planned_landing_md: f64,
+}
+
+/// Formats a tool face as a driller's clock position (12 = high side).
+pub fn clock_position(tool_face: f64) -> String {
+    let half_hours = ((tool_face / 15.0).round() as i64).rem_euclid(24);
+    let hours = match half_hours / 2 {
+        0 => 12,
+        h => h,
+    };
+    format!("{}:{:02}", hours, if half_hours % 2 == 1 { 30 } else { 0 })
+}
+
+fn dot(a: [f64; 3], b: [f64; 3]) -> f64 {
+    (0..3).map(|k| a[k] * b[k]).sum()
+}
+
+/// Unit (north, east, down) tangent, high-side and right-side vectors.
+fn frame(pitch: f64, azimuth: f64) -> [[f64; 3]; 3] {
+    let (p, az) = (pitch.to_radians(), azimuth.to_radians());
+    [
+        [p.cos() * az.cos(), p.cos() * az.sin(), p.sin()],
+        [p.sin() * az.cos(), p.sin() * az.sin(), -p.cos()],
+        [-az.sin(), az.cos(), 0.0],
+    ]
+}
+
+/// Recommends a two-phase correction that rejoins the plan from the last
+/// actual station.
+///
+/// Relative to the plan, the bore is steered with one constant curvature
+/// and then an opposite one over the same number of rods, so that it lands
+/// on the plan heading along it. Curvature is solved in the plan's
+/// high-side/right-side frame with small-angle geometry, allowing for the
+/// plan's own curvature, and the shortest correction within the rig's
+/// build/turn rates is chosen. Partial rates are held by steering part of
+/// each rod.
+pub fn advise_steering(
+    actual: &[SurveyPoint],
+    planned: &[SurveyPoint],
+    capability: &SteeringCapability,
+) -> Result<SteeringAdvice, SteeringError> {
+    let rod = capability.rod_length_ft;
+    if !(rod > 0.0 && capability.build_per_rod_deg > 0.0 && capability.turn_per_rod_deg > 0.0) {
+        return Err(SteeringError::InvalidCapability);
+    }
+    let plan = SurveyPath::new(planned)?;
+    let coords = calculate_path(actual)?;
+    let (current, position) = (&actual[actual.len() - 1], &coords[coords.len() - 1]);
+
+    let nearest = plan.nearest_md(position).station;
+    let [_, high_side, right_side] = frame(nearest.pitch, nearest.azimuth);
+    let offset = [
+        position.north - nearest.north,
+        position.east - nearest.east,
+        position.tvd - nearest.tvd,
+    ];
+    let heading = frame(current.inclination, current.azimuth)[0];
+    // Offsets and slopes as (high, right).
+    let y = [dot(offset, high_side), dot(offset, right_side)];
+    let a = [dot(heading, high_side), dot(heading, right_side)];
+
+    let mut advice = SteeringAdvice {
+        measured_depth: current.measured_depth,
+        high: y[0],
+        right: y[1],
+        phases: Vec::new(),
+        landing_md: current.measured_depth,
+        planned_landing_md: nearest.measured_depth,
+    };
+    if y[0].hypot(y[1]) < ON_PLAN_OFFSET_FT && a[0].hypot(a[1]) < ON_PLAN_ANGLE {
+        return Ok(advice);
+    }
+
+    // Plan curvature around the nearest point, in the same frame.
+    let delta = rod.min((plan.end_md() - plan.start_md()) / 2.0);
+    let md_before = (nearest.measured_depth - delta).max(plan.start_md());
+    let md_after = (md_before + delta).min(plan.end_md());
+    let plan_curvature = match (plan.at(md_before), plan.at(md_after)) {
+        (Some(s1), Some(s2)) if md_after > md_before => {
+            let (t1, t2) = (
+                frame(s1.pitch, s1.azimuth)[0],
+                frame(s2.pitch, s2.azimuth)[0],
+            );
+            let dt = [0, 1, 2].map(|k| (t2[k] - t1[k]) / (md_after - md_before));
+            [dot(dt, high_side), dot(dt, right_side)]
+        }
+        _ => [0.0; 2],
+    };
+
+    let max_build = capability.build_per_rod_deg.to_radians() / rod;
+    let max_turn = capability.turn_per_rod_deg.to_radians() / rod;
+    let usage = |k: [f64; 2]| (k[0] / max_build).hypot(k[1] / max_turn);
+    for n in 1..=capability.max_rods / 2 {
+        let s = n as f64 * rod;
+        let first = [0, 1].map(|i| -(y[i] + 1.5 * a[i] * s) / (s * s) + plan_curvature[i]);
+        let second = [0, 1].map(|i| (y[i] + 0.5 * a[i] * s) / (s * s) + plan_curvature[i]);
+        if usage(first) > 1.0 || usage(second) > 1.0 {
+            continue;
+        }
+        let mut md = current.measured_depth;
+        for k in [first, second] {
+            let tool_face = k[1].atan2(k[0]).to_degrees().rem_euclid(360.0);
+            advice.phases.push(SteeringPhase {
+                tool_face,
+                clock: clock_position(tool_face),
+                rods: n,
+                steer_fraction: usage(k),
+                start_md: md,
+                end_md: md + s,
+            });
+            md += s;
+        }
+        advice.landing_md = md;
+        advice.planned_landing_md = (nearest.measured_depth + 2.0 * s).min(plan.end_md());
+        return Ok(advice);
+    }
+    Err(SteeringError::NoSolution {
+        max_rods: capability.max_rods,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::deviation::{deviation_report, ToleranceTube};
+
+    fn station(md: f64, pitch: f64, azimuth: f64) -> SurveyPoint {
+        SurveyPoint {
+            measured_depth: md,
+            inclination: pitch,
+            azimuth,
+        }
+    }
+
+    fn plan() -> Vec<SurveyPoint> {
+        vec![station(0.0, 0.0, 90.0), station(1000.0, 0.0, 90.0)]
+    }
+
+    /// Drills the advised phases rod by rod. A fully steered rod bends along
+    /// the tool face as far as the build/turn ellipse allows.
+    fn drill(
+        actual: &[SurveyPoint],
+        advice: &SteeringAdvice,
+        capability: &SteeringCapability,
+    ) -> Vec<SurveyPoint> {
+        let mut surveys = actual.to_vec();
+        let (max_build, max_turn) = (capability.build_per_rod_deg, capability.turn_per_rod_deg);
+        for phase in &advice.phases {
+            let face = phase.tool_face.to_radians();
+            let reach = 1.0 / (face.cos() / max_build).hypot(face.sin() / max_turn);
+            for _ in 0..phase.rods {
+                let last = surveys.last().unwrap().clone();
+                let build = phase.steer_fraction * reach * face.cos();
+                let turn = phase.steer_fraction * reach * face.sin();
+                surveys.push(station(
+                    last.measured_depth + capability.rod_length_ft,
+                    last.inclination - build,
+                    last.azimuth + turn,
+                ));
+            }
+        }
+        surveys
+    }
+
+    #[test]
+    fn test_correction_rejoins_plan() {
+        // 3 ft high, 2 ft left and diving back down at 1.5 degrees.
+        let actual = vec![
+            station(0.0, -1.0, 89.0),
+            station(200.0, -0.72, 89.42),
+            station(210.0, 1.5, 90.0),
+        ];
+        let advice = advise_steering(&actual, &plan(), &SteeringCapability::default()).unwrap();
+        assert!(advice.high > 0.0 && advice.right < 0.0);
+        assert_eq!(advice.phases.len(), 2);
+        assert_eq!(advice.phases[0].rods, advice.phases[1].rods);
+        assert!(advice.phases.iter().all(|p| p.steer_fraction <= 1.0));
+        assert_eq!(advice.landing_md, advice.phases[1].end_md);
+
+        let drilled = drill(&actual, &advice, &SteeringCapability::default());
+        assert_eq!(drilled.last().unwrap().measured_depth, advice.landing_md);
+        let report = deviation_report(&drilled, &plan(), &ToleranceTube::default()).unwrap();
+        let landed = report.stations.last().unwrap();
+        assert!(landed.distance < 0.25, "{landed:?}");
+        assert!(landed.pitch_difference.abs() < 0.1);
+        assert!(landed.azimuth_difference.abs() < 0.1);
+        assert!((landed.planned_md - advice.planned_landing_md).abs() < 1.0);
+    }
+
+    #[test]
+    fn test_uneven_build_and_turn_rates() {
+        // 2 ft right and 1 ft low on a rig that builds far more readily than
+        // it turns.
+        let actual = vec![
+            station(0.0, 0.5, 90.6),
+            station(200.0, 0.3, 90.5),
+            station(210.0, 0.0, 90.0),
+        ];
+        let capability = SteeringCapability {
+            rod_length_ft: 15.0,
+            build_per_rod_deg: 2.0,
+            turn_per_rod_deg: 0.5,
+            max_rods: 40,
+        };
+        let advice = advise_steering(&actual, &plan(), &capability).unwrap();
+        assert_eq!(advice.phases.len(), 2);
+        let drilled = drill(&actual, &advice, &capability);
+
+        // Each phase's share of the rig's build and turn adds up, as an
+        // ellipse, to its steer fraction; the small turn rate dominates.
+        let mut first_rod = actual.len();
+        for phase in &advice.phases {
+            let (before, after) = (&drilled[first_rod - 1], &drilled[first_rod]);
+            let build = (before.inclination - after.inclination) / capability.build_per_rod_deg;
+            let turn = (after.azimuth - before.azimuth) / capability.turn_per_rod_deg;
+            assert!((build.hypot(turn) - phase.steer_fraction).abs() < 1e-9);
+            assert!(turn.abs() > build.abs());
+            assert!(phase.steer_fraction <= 1.0);
+            first_rod += phase.rods;
+        }
+
+        let report = deviation_report(&drilled, &plan(), &ToleranceTube::default()).unwrap();
+        let landed = report.stations.last().unwrap();
+        assert_eq!(landed.measured_depth, advice.landing_md);
+        assert!(landed.distance < 0.25, "{landed:?}");
+        assert!(landed.pitch_difference.abs() < 0.1);
+        assert!(landed.azimuth_difference.abs() < 0.1);
+    }
+
+    #[test]
+    fn test_on_plan_and_unreachable() {
+        let on_plan = vec![station(0.0, 0.0, 90.0), station(100.0, 0.0, 90.0)];
+        let advice = advise_steering(&on_plan, &plan(), &SteeringCapability::default()).unwrap();
+        assert!(advice.phases.is_empty());
+        assert_eq!(advice.landing_md, 100.0);
+
+        let off = vec![station(0.0, 0.0, 90.0), station(100.0, 0.0, 100.0)];
+        let stiff = SteeringCapability {
+            build_per_rod_deg: 0.05,
+            turn_per_rod_deg: 0.05,
+            max_rods: 10,
+            ..Default::default()
+        };
+        assert_eq!(
+            advise_steering(&off, &plan(), &stiff),
+            Err(SteeringError::NoSolution { max_rods: 10 })
+        );
+    }
+
+    #[test]
+    fn test_clock_positions() {
+        assert_eq!(clock_position(0.0), "12:00");
+        assert_eq!(clock_position(90.0), "3:00");
+        assert_eq!(clock_position(180.0), "6:00");
+        assert_eq!(clock_position(315.0), "10:30");
+        assert_eq!(clock_position(355.0), "12:00");
+    }
+}
@@ -0,0 +1,101 @@
+use chrono::{DateTime, FixedOffset, NaiveDateTime, SecondsFormat, Utc};
+
+/// Plain date-time layouts accepted besides RFC 3339, as written by CSV
+/// imports and WITS0 feeds without a configured offset.
+const NAIVE_FORMATS: [&str; 2] = ["%Y-%m-%d %H:%M:%S%.f", "%Y-%m-%dT%H:%M:%S%.f"];
+
+/// How timestamps without a UTC offset are placed on the UTC time line.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum NaiveTimes {
+    /// Read as UTC.
+    #[default]
+    Utc,
+    /// Read as local time at a fixed offset from UTC, e.g. a rig clock.
+    Offset(FixedOffset),
+    /// Not accepted; only RFC 3339 timestamps parse.
+    Reject,
+}
+
+/// Parses a telemetry timestamp: RFC 3339, or a plain `YYYY-MM-DD HH:MM:SS`
+/// (space or `T` separated, optional fraction) placed by `naive`.
+pub fn parse_timestamp(timestamp: &str, naive: NaiveTimes) -> Option<DateTime<Utc>> {
+    let timestamp = timestamp.trim();
+    if let Ok(t) = DateTime::parse_from_rfc3339(timestamp) {
+        return Some(t.with_timezone(&Utc));
+    }
+    let local = NAIVE_FORMATS
+        .iter()
+        .find_map(|f| NaiveDateTime::parse_from_str(timestamp, f).ok())?;
+    match naive {
+        NaiveTimes::Utc => Some(local.and_utc()),
+        NaiveTimes::Offset(offset) => local.checked_sub_offset(offset).map(|t| t.and_utc()),
+        NaiveTimes::Reject => None,
+    }
+}
+
+/// Formats a time parsed from `like` (or interpolated between such times) in
+/// the same style: RFC 3339 UTC if `like` carries an offset, otherwise a
+/// plain date-time with `like`'s separator, read back with [`NaiveTimes::Utc`].
+pub fn format_timestamp_like(time: DateTime<Utc>, like: &str) -> String {
+    if DateTime::parse_from_rfc3339(like.trim()).is_ok() {
+        return time.to_rfc3339_opts(SecondsFormat::AutoSi, true);
+    }
+    let layout = if like.contains('T') {
+        NAIVE_FORMATS[1]
+    } else {
+        NAIVE_FORMATS[0]
+    };
+    time.format(layout).to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_naive_time_policies() {
+        let utc = |s| parse_timestamp(s, NaiveTimes::Utc).map(|t| t.to_rfc3339());
+        assert_eq!(
+            utc("2024-05-01T10:00:00-05:00").as_deref(),
+            Some("2024-05-01T15:00:00+00:00")
+        );
+        assert_eq!(
+            utc("2024-05-01 10:00:00.5").as_deref(),
+            Some("2024-05-01T10:00:00.500+00:00")
+        );
+        assert_eq!(utc("01/05/2024 10:00"), None);
+
+        let rig = NaiveTimes::Offset(FixedOffset::west_opt(5 * 3600).unwrap());
+        let t = parse_timestamp("2024-05-01T22:30:00", rig).unwrap();
+        assert_eq!(t.to_rfc3339(), "2024-05-02T03:30:00+00:00");
+        // Zoned timestamps ignore the policy.
+        assert_eq!(
+            parse_timestamp("2024-05-01T22:30:00Z", rig)
+                .unwrap()
+                .to_rfc3339(),
+            "2024-05-01T22:30:00+00:00"
+        );
+        assert_eq!(
+            parse_timestamp("2024-05-01 22:30:00", NaiveTimes::Reject),
+            None
+        );
+    }
+
+    #[test]
+    fn test_format_like_source() {
+        let t = parse_timestamp("2024-05-01 10:00:00", NaiveTimes::Utc).unwrap();
+        assert_eq!(
+            format_timestamp_like(t, "2024-05-01 09:00:00"),
+            "2024-05-01 10:00:00"
+        );
+        assert_eq!(
+            format_timestamp_like(t, "2024-05-01T09:00:00"),
+            "2024-05-01T10:00:00"
+        );
+        let t = t + chrono::Duration::milliseconds(250);
+        assert_eq!(
+            format_timestamp_like(t, "2024-05-01T09:00:00Z"),
+            "2024-05-01T10:00:00.250Z"
+        );
+    }
+}
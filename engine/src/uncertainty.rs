@@ -0,0 +1,400 @@
+use crate::{course_displacement, validate_stations, SurveyError, SurveyMethod, SurveyPoint};
+use serde::{Deserialize, Serialize};
+
+type Vector = [f64; 3];
+/// Covariance of (north, east, TVD) in ft².
+pub type Covariance = [[f64; 3]; 3];
+
+/// One-sigma sensor and measurement errors.
+///
+/// "Random" terms are independent at each station; "bias" terms are the
+/// same at every station and so accumulate along the bore.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ErrorModel {
+    /// Pitch sensor noise (degrees).
+    pub pitch_random_deg: f64,
+    /// Pitch sensor calibration offset (degrees).
+    pub pitch_bias_deg: f64,
+    /// Azimuth reading noise (degrees).
+    pub azimuth_random_deg: f64,
+    /// Magnetic azimuth error from declination or site interference (degrees).
+    pub azimuth_bias_deg: f64,
+    /// Length error per course (ft), covering rod-count and joint slop.
+    pub md_per_course_ft: f64,
+    /// Proportional MD error, e.g. from nominal vs actual rod length.
+    pub md_scale_fraction: f64,
+    /// Walkover locator depth error as a fraction of depth. When set, each
+    /// station's vertical uncertainty is narrowed by a locator reading.
+    pub locator_depth_fraction: Option<f64>,
+    /// Floor on the locator depth error (ft).
+    pub locator_min_error_ft: f64,
+}
+
+impl Default for ErrorModel {
+    fn default() -> Self {
+        Self {
+            pitch_random_deg: 0.1,
+            pitch_bias_deg: 0.1,
+            azimuth_random_deg: 0.5,
+            azimuth_bias_deg: 1.0,
+            md_per_course_ft: 0.1,
+            md_scale_fraction: 0.001,
+            locator_depth_fraction: Some(0.05),
+            locator_min_error_ft: 0.25,
+        }
+    }
+}
+
+/// Position covariance at one survey station.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct StationCovariance {
+    pub measured_depth: f64,
+    pub tvd: f64,
+    pub covariance: Covariance,
+}
+
+/// Uncertainty at a confidence level: a horizontal ellipse plus a vertical
+/// half-width, all in ft.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct UncertaintyEllipse {
+    pub confidence: f64,
+    pub semi_major: f64,
+    pub semi_minor: f64,
+    /// Azimuth of the major axis, 0..180 degrees.
+    pub major_azimuth: f64,
+    /// Half-width of the vertical interval.
+    pub vertical: f64,
+}
+
+impl StationCovariance {
+    /// Ellipse containing the true position with probability `confidence`
+    /// (e.g. 0.95), or `None` if `confidence` isn't strictly between 0 and 1.
+    pub fn ellipse(&self, confidence: f64) -> Option<UncertaintyEllipse> {
+        if !(confidence > 0.0 && confidence < 1.0) {
+            return None;
+        }
+        let [[nn, ne, _], [_, ee, _], [_, _, vv]] = self.covariance;
+        let mean = (nn + ee) / 2.0;
+        let spread = (((nn - ee) / 2.0).powi(2) + ne * ne).sqrt();
+        // Chi-squared with two degrees of freedom has a closed-form quantile.
+        let k2 = (-2.0 * (1.0 - confidence).ln()).sqrt();
+        let k1 = normal_quantile((1.0 + confidence) / 2.0);
+        Some(UncertaintyEllipse {
+            confidence,
+            semi_major: k2 * (mean + spread).max(0.0).sqrt(),
+            semi_minor: k2 * (mean - spread).max(0.0).sqrt(),
+            major_azimuth: (0.5 * (2.0 * ne).atan2(nn - ee))
+                .to_degrees()
+                .rem_euclid(180.0),
+            vertical: k1 * vv.max(0.0).sqrt(),
+        })
+    }
+
+    /// Half-width of the `confidence` interval along a (north, east, TVD)
+    /// direction, which need not be normalised.
+    pub fn half_width_along(&self, direction: [f64; 3], confidence: f64) -> Option<f64> {
+        let length = direction.iter().map(|d| d * d).sum::<f64>().sqrt();
+        if !(confidence > 0.0 && confidence < 1.0) || length == 0.0 {
+            return None;
+        }
+        let u = direction.map(|d| d / length);
+        let variance: f64 = (0..3)
+            .flat_map(|i| (0..3).map(move |j| (i, j)))
+            .map(|(i, j)| u[i] * self.covariance[i][j] * u[j])
+            .sum();
+        Some(normal_quantile((1.0 + confidence) / 2.0) * variance.max(0.0).sqrt())
+    }
+}
+
+fn sub(a: Vector, b: Vector) -> Vector {
+    [a[0] - b[0], a[1] - b[1], a[2] - b[2]]
+}
+
+fn add(a: Vector, b: Vector) -> Vector {
+    [a[0] + b[0], a[1] + b[1], a[2] + b[2]]
+}
+
+fn add_outer(cov: &mut Covariance, d: Vector) {
+    for (i, row) in cov.iter_mut().enumerate() {
+        for (j, cell) in row.iter_mut().enumerate() {
+            *cell += d[i] * d[j];
+        }
+    }
+}
+
+fn courses(surveys: &[SurveyPoint]) -> Vec<Vector> {
+    surveys
+        .windows(2)
+        .map(|w| {
+            let (n, e, v) = course_displacement(&w[0], &w[1], SurveyMethod::MinimumCurvature);
+            [n, e, v]
+        })
+        .collect()
+}
+
+/// Propagates `model` through the minimum-curvature path.
+///
+/// Each error term is linearised: the path is recomputed with the term
+/// perturbed by one sigma, and the resulting position shifts are summed as
+/// outer products. Locator readings tighten the reported vertical
+/// uncertainty at each station but don't feed into later stations.
+pub fn propagate_uncertainty(
+    surveys: &[SurveyPoint],
+    model: &ErrorModel,
+) -> Result<Vec<StationCovariance>, SurveyError> {
+    validate_stations(surveys)?;
+    let n = surveys.len();
+    let base = courses(surveys);
+    let mut positions = vec![[0.0; 3]; n];
+    for k in 1..n {
+        positions[k] = add(positions[k - 1], base[k - 1]);
+    }
+    let mut covs = vec![[[0.0; 3]; 3]; n];
+
+    // Bias terms shift every station together.
+    let biased = |f: &dyn Fn(&SurveyPoint) -> SurveyPoint| -> Vec<Vector> {
+        let perturbed: Vec<SurveyPoint> = surveys.iter().map(f).collect();
+        let mut shift = [0.0; 3];
+        let mut shifts = vec![shift];
+        for (new, old) in courses(&perturbed).iter().zip(&base) {
+            shift = add(shift, sub(*new, *old));
+            shifts.push(shift);
+        }
+        shifts
+    };
+    let bias_terms = [
+        biased(&|s| SurveyPoint {
+            inclination: s.inclination + model.pitch_bias_deg,
+            ..s.clone()
+        }),
+        biased(&|s| SurveyPoint {
+            azimuth: s.azimuth + model.azimuth_bias_deg,
+            ..s.clone()
+        }),
+        positions
+            .iter()
+            .map(|p| p.map(|x| x * model.md_scale_fraction))
+            .collect(),
+    ];
+    for shifts in &bias_terms {
+        for (cov, d) in covs.iter_mut().zip(shifts) {
+            add_outer(cov, *d);
+        }
+    }
+
+    // Random station terms move only the two courses touching the station;
+    // everything after the station moves with them.
+    for j in 0..n {
+        for perturb in [
+            |s: &SurveyPoint, m: &ErrorModel| SurveyPoint {
+                inclination: s.inclination + m.pitch_random_deg,
+                ..s.clone()
+            },
+            |s: &SurveyPoint, m: &ErrorModel| SurveyPoint {
+                azimuth: s.azimuth + m.azimuth_random_deg,
+                ..s.clone()
+            },
+        ] {
+            let moved = perturb(&surveys[j], model);
+            let before = if j > 0 {
+                let (dn, de, dv) =
+                    course_displacement(&surveys[j - 1], &moved, SurveyMethod::MinimumCurvature);
+                sub([dn, de, dv], base[j - 1])
+            } else {
+                [0.0; 3]
+            };
+            let after = if j + 1 < n {
+                let (dn, de, dv) =
+                    course_displacement(&moved, &surveys[j + 1], SurveyMethod::MinimumCurvature);
+                sub([dn, de, dv], base[j])
+            } else {
+                [0.0; 3]
+            };
+            add_outer(&mut covs[j], before);
+            let total = add(before, after);
+            for cov in covs.iter_mut().skip(j + 1) {
+                add_outer(cov, total);
+            }
+        }
+
+        // Random length error on the course ending at station j.
+        if j > 0 {
+            let length = surveys[j].measured_depth - surveys[j - 1].measured_depth;
+            let d = base[j - 1].map(|x| x * model.md_per_course_ft / length);
+            for cov in covs.iter_mut().skip(j) {
+                add_outer(cov, d);
+            }
+        }
+    }
+
+    if let Some(fraction) = model.locator_depth_fraction {
+        for (cov, p) in covs.iter_mut().zip(&positions) {
+            let sigma = (fraction * p[2].abs()).max(model.locator_min_error_ft);
+            let denominator = cov[2][2] + sigma * sigma;
+            if denominator > 0.0 {
+                let column = [cov[0][2], cov[1][2], cov[2][2]];
+                for i in 0..3 {
+                    for j in 0..3 {
+                        cov[i][j] -= column[i] * column[j] / denominator;
+                    }
+                }
+            }
+        }
+    }
+
+    Ok(surveys
+        .iter()
+        .zip(covs)
+        .zip(&positions)
+        .map(|((s, covariance), p)| StationCovariance {
+            measured_depth: s.measured_depth,
+            tvd: p[2],
+            covariance,
+        })
+        .collect())
+}
+
+/// Inverse of the standard normal CDF (Acklam's rational approximation,
+/// relative error below 1.2e-9).
+fn normal_quantile(p: f64) -> f64 {
+    const A: [f64; 6] = [
+        -3.969_683_028_665_376e1,
+        2.209_460_984_245_205e2,
+        -2.759_285_104_469_687e2,
+        1.383_577_518_672_69e2,
+        -3.066_479_806_614_716e1,
+        2.506_628_277_459_239,
+    ];
+    const B: [f64; 5] = [
+        -5.447_609_879_822_406e1,
+        1.615_858_368_580_409e2,
+        -1.556_989_798_598_866e2,
+        6.680_131_188_771_972e1,
+        -1.328_068_155_288_572e1,
+    ];
+    const C: [f64; 6] = [
+        -7.784_894_002_430_293e-3,
+        -3.223_964_580_411_365e-1,
+        -2.400_758_277_161_838,
+        -2.549_732_539_343_734,
+        4.374_664_141_464_968,
+        2.938_163_982_698_783,
+    ];
+    const D: [f64; 4] = [
+        7.784_695_709_041_462e-3,
+        3.224_671_290_700_398e-1,
+        2.445_134_137_142_996,
+        3.754_408_661_907_416,
+    ];
+    let tail = |q: f64| {
+        (((((C[0] * q + C[1]) * q + C[2]) * q + C[3]) * q + C[4]) * q + C[5])
+            / ((((D[0] * q + D[1]) * q + D[2]) * q + D[3]) * q + 1.0)
+    };
+    const P_LOW: f64 = 0.024_25;
+    if p < P_LOW {
+        tail((-2.0 * p.ln()).sqrt())
+    } else if p > 1.0 - P_LOW {
+        -tail((-2.0 * (1.0 - p).ln()).sqrt())
+    } else {
+        let q = p - 0.5;
+        let r = q * q;
+        (((((A[0] * r + A[1]) * r + A[2]) * r + A[3]) * r + A[4]) * r + A[5]) * q
+            / (((((B[0] * r + B[1]) * r + B[2]) * r + B[3]) * r + B[4]) * r + 1.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn straight_bore(stations: usize) -> Vec<SurveyPoint> {
+        (0..stations)
+            .map(|i| SurveyPoint {
+                measured_depth: i as f64 * 10.0,
+                inclination: 0.0,
+                azimuth: 0.0,
+            })
+            .collect()
+    }
+
+    fn only(model: ErrorModel) -> ErrorModel {
+        ErrorModel {
+            pitch_random_deg: 0.0,
+            pitch_bias_deg: 0.0,
+            azimuth_random_deg: 0.0,
+            azimuth_bias_deg: 0.0,
+            md_per_course_ft: 0.0,
+            md_scale_fraction: 0.0,
+            locator_depth_fraction: None,
+            ..model
+        }
+    }
+
+    #[test]
+    fn test_azimuth_bias_grows_linearly() {
+        let model = ErrorModel {
+            azimuth_bias_deg: 1.0,
+            ..only(ErrorModel::default())
+        };
+        let covs = propagate_uncertainty(&straight_bore(11), &model).unwrap();
+        assert_eq!(covs[0].covariance, [[0.0; 3]; 3]);
+        let sigma_east = covs[10].covariance[1][1].sqrt();
+        assert!((sigma_east - 100.0 * 1f64.to_radians().sin()).abs() < 1e-6);
+
+        let ellipse = covs[10].ellipse(0.95).unwrap();
+        let k = (-2.0 * 0.05f64.ln()).sqrt();
+        // A one-degree swing moves the end along a chord bearing 090.5.
+        let chord = 200.0 * 0.5f64.to_radians().sin();
+        assert!((ellipse.semi_major - k * chord).abs() < 1e-6);
+        assert!(ellipse.semi_minor.abs() < 1e-6);
+        assert!((ellipse.major_azimuth - 90.5).abs() < 1e-6);
+        assert!(covs[10].ellipse(1.0).is_none());
+    }
+
+    #[test]
+    fn test_random_pitch_accumulates_as_random_walk() {
+        let model = ErrorModel {
+            pitch_random_deg: 0.5,
+            ..only(ErrorModel::default())
+        };
+        let bore = straight_bore(101);
+        let covs = propagate_uncertainty(&bore, &model).unwrap();
+        let v25 = covs[25].covariance[2][2];
+        let v100 = covs[100].covariance[2][2];
+        // Independent station errors: variance grows with station count.
+        assert!((v100 / v25 - 4.0).abs() < 0.1);
+        assert!(covs[100].covariance[1][1].abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_locator_caps_vertical_uncertainty() {
+        let bore: Vec<_> = (0..51)
+            .map(|i| SurveyPoint {
+                measured_depth: i as f64 * 10.0,
+                inclination: 5.0,
+                azimuth: 90.0,
+            })
+            .collect();
+        let without = propagate_uncertainty(
+            &bore,
+            &ErrorModel {
+                locator_depth_fraction: None,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        let with = propagate_uncertainty(&bore, &ErrorModel::default()).unwrap();
+        let last = &with[50];
+        let locator_sigma = 0.05 * last.tvd;
+        assert!(last.covariance[2][2].sqrt() <= locator_sigma + 1e-9);
+        assert!(last.covariance[2][2] < without[50].covariance[2][2]);
+        assert!(last.ellipse(0.95).unwrap().vertical > 0.0);
+    }
+
+    #[test]
+    fn test_normal_quantile() {
+        assert!(normal_quantile(0.5).abs() < 1e-9);
+        assert!((normal_quantile(0.975) - 1.959_963_985).abs() < 1e-6);
+        assert!((normal_quantile(0.005) + 2.575_829_304).abs() < 1e-6);
+    }
+}
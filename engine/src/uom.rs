@@ -0,0 +1,202 @@
+use crate::witsml_parser::WitsmlError;
+use std::fmt;
+
+/// Physical quantity a unit of measure belongs to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Quantity {
+    Length,
+    Force,
+    Torque,
+    Pressure,
+    FlowRate,
+    Density,
+    Angle,
+    RotationalSpeed,
+}
+
+impl Quantity {
+    /// The internal unit every value of this quantity is normalised to.
+    pub fn canonical_unit(self) -> &'static str {
+        match self {
+            Quantity::Length => "ft",
+            Quantity::Force => "lbf",
+            Quantity::Torque => "ft.lbf",
+            Quantity::Pressure => "psi",
+            Quantity::FlowRate => "gal/min",
+            Quantity::Density => "lbm/gal",
+            Quantity::Angle => "dega",
+            Quantity::RotationalSpeed => "rpm",
+        }
+    }
+}
+
+impl fmt::Display for Quantity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Quantity::Length => "length",
+            Quantity::Force => "force",
+            Quantity::Torque => "torque",
+            Quantity::Pressure => "pressure",
+            Quantity::FlowRate => "flow rate",
+            Quantity::Density => "density",
+            Quantity::Angle => "angle",
+            Quantity::RotationalSpeed => "rotational speed",
+        };
+        f.write_str(name)
+    }
+}
+
+/// A known unit: multiplying a value by `factor` yields the canonical unit.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Unit {
+    pub symbol: &'static str,
+    pub quantity: Quantity,
+    pub factor: f64,
+}
+
+impl Unit {
+    /// Converts a value in this unit to the canonical unit of its quantity.
+    pub fn to_canonical(&self, value: f64) -> f64 {
+        value * self.factor
+    }
+
+    /// Converts a canonical value back into this unit.
+    pub fn from_canonical(&self, value: f64) -> f64 {
+        value / self.factor
+    }
+}
+
+const fn unit(symbol: &'static str, quantity: Quantity, factor: f64) -> Unit {
+    Unit {
+        symbol,
+        quantity,
+        factor,
+    }
+}
+
+/// Registry of supported units. Symbols are matched case-insensitively.
+const UNITS: &[Unit] = &[
+    // Length -> ft
+    unit("ft", Quantity::Length, 1.0),
+    unit("m", Quantity::Length, 1.0 / 0.3048),
+    unit("in", Quantity::Length, 1.0 / 12.0),
+    unit("cm", Quantity::Length, 0.01 / 0.3048),
+    unit("mm", Quantity::Length, 0.001 / 0.3048),
+    // Force -> lbf
+    unit("lbf", Quantity::Force, 1.0),
+    unit("klbf", Quantity::Force, 1000.0),
+    unit("N", Quantity::Force, 0.224_808_943),
+    unit("daN", Quantity::Force, 2.248_089_43),
+    unit("kN", Quantity::Force, 224.808_943),
+    unit("kgf", Quantity::Force, 2.204_622_62),
+    // Torque -> ft.lbf
+    unit("ft.lbf", Quantity::Torque, 1.0),
+    unit("lbf.ft", Quantity::Torque, 1.0),
+    unit("kft.lbf", Quantity::Torque, 1000.0),
+    unit("N.m", Quantity::Torque, 0.737_562_149),
+    unit("daN.m", Quantity::Torque, 7.375_621_49),
+    unit("kN.m", Quantity::Torque, 737.562_149),
+    // Pressure -> psi
+    unit("psi", Quantity::Pressure, 1.0),
+    unit("Pa", Quantity::Pressure, 0.000_145_037_738),
+    unit("kPa", Quantity::Pressure, 0.145_037_738),
+    unit("MPa", Quantity::Pressure, 145.037_738),
+    unit("bar", Quantity::Pressure, 14.503_773_8),
+    // Flow rate -> gal/min (US)
+    unit("gal/min", Quantity::FlowRate, 1.0),
+    unit("galUS/min", Quantity::FlowRate, 1.0),
+    unit("gpm", Quantity::FlowRate, 1.0),
+    unit("bbl/min", Quantity::FlowRate, 42.0),
+    unit("L/min", Quantity::FlowRate, 0.264_172_052),
+    unit("L/s", Quantity::FlowRate, 15.850_323_1),
+    unit("m3/min", Quantity::FlowRate, 264.172_052),
+    unit("m3/h", Quantity::FlowRate, 4.402_867_54),
+    // Density -> lbm/gal (ppg)
+    unit("lbm/gal", Quantity::Density, 1.0),
+    unit("lbm/galUS", Quantity::Density, 1.0),
+    unit("ppg", Quantity::Density, 1.0),
+    unit("g/cm3", Quantity::Density, 8.345_404_45),
+    unit("kg/m3", Quantity::Density, 0.008_345_404_45),
+    unit("sg", Quantity::Density, 8.345_404_45),
+    // Angle -> degrees
+    unit("dega", Quantity::Angle, 1.0),
+    unit("deg", Quantity::Angle, 1.0),
+    unit("rad", Quantity::Angle, 180.0 / std::f64::consts::PI),
+    // Rotational speed -> rpm
+    unit("rpm", Quantity::RotationalSpeed, 1.0),
+    unit("c/min", Quantity::RotationalSpeed, 1.0),
+    unit(
+        "rad/s",
+        Quantity::RotationalSpeed,
+        30.0 / std::f64::consts::PI,
+    ),
+];
+
+/// Looks up a unit by its uom symbol.
+///
+/// Accepts the common spellings seen in rig exports (`ft·lbf`, `ft-lbf`,
+/// `ft*lbf`) as well as the WITSML `ft.lbf` form.
+pub fn lookup(symbol: &str) -> Result<Unit, WitsmlError> {
+    let normalised: String = symbol
+        .trim()
+        .chars()
+        .map(|c| match c {
+            '·' | '-' | '*' => '.',
+            other => other,
+        })
+        .collect();
+
+    UNITS
+        .iter()
+        .find(|u| u.symbol.eq_ignore_ascii_case(&normalised))
+        .copied()
+        .ok_or_else(|| WitsmlError::UnknownUnit(symbol.to_string()))
+}
+
+/// Looks up a unit and checks it measures the expected quantity.
+///
+/// An empty symbol means the value is already in the canonical unit.
+pub fn lookup_for(symbol: &str, expected: Quantity) -> Result<Unit, WitsmlError> {
+    if symbol.trim().is_empty() {
+        return lookup(expected.canonical_unit());
+    }
+    let unit = lookup(symbol)?;
+    if unit.quantity != expected {
+        return Err(WitsmlError::IncompatibleUnit {
+            unit: symbol.to_string(),
+            expected,
+        });
+    }
+    Ok(unit)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_metric_conversions() {
+        let m = lookup("m").unwrap();
+        assert!((m.to_canonical(0.3048) - 1.0).abs() < 1e-12);
+
+        let knm = lookup("kN·m").unwrap();
+        assert_eq!(knm.quantity, Quantity::Torque);
+        assert!((knm.to_canonical(1.0) - 737.562149).abs() < 1e-6);
+
+        let bar = lookup("bar").unwrap();
+        assert!((bar.to_canonical(10.0) - 145.037738).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_unknown_and_incompatible_units() {
+        assert!(matches!(
+            lookup("furlong"),
+            Err(WitsmlError::UnknownUnit(_))
+        ));
+        assert!(matches!(
+            lookup_for("psi", Quantity::Torque),
+            Err(WitsmlError::IncompatibleUnit { .. })
+        ));
+        assert_eq!(lookup_for("", Quantity::Length).unwrap().symbol, "ft");
+    }
+}
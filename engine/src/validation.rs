@@ -0,0 +1,489 @@
+use crate::timestamp::{parse_timestamp, NaiveTimes};
+use crate::witsml_parser::{TelemetryData, DEFAULT_NULL_VALUE};
+use crate::SurveyPoint;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Severity {
+    Info,
+    Warning,
+    Error,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FindingKind {
+    /// A `-999.25` placeholder survived import as a real value.
+    NullPlaceholder,
+    /// NaN or infinite reading.
+    NonFinite,
+    DepthReversal,
+    FrozenSensor,
+    Spike,
+    TimestampGap,
+    TimestampReversal,
+    UnparseableTimestamp,
+    DuplicateMeasuredDepth,
+    MeasuredDepthDecrease,
+    PitchOutOfRange,
+    PitchJump,
+}
+
+/// One data-quality problem, located by row (or station) index.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Finding {
+    pub kind: FindingKind,
+    pub row: usize,
+    pub channel: Option<&'static str>,
+    pub severity: Severity,
+    pub message: String,
+}
+
+/// Findings for one import, ready to show in an import report.
+#[derive(Debug, Clone, PartialEq, Default, Serialize)]
+pub struct ValidationReport {
+    pub rows_checked: usize,
+    pub findings: Vec<Finding>,
+}
+
+impl ValidationReport {
+    pub fn count(&self, severity: Severity) -> usize {
+        self.findings
+            .iter()
+            .filter(|f| f.severity == severity)
+            .count()
+    }
+
+    pub fn has_errors(&self) -> bool {
+        self.count(Severity::Error) > 0
+    }
+
+    fn push(
+        &mut self,
+        kind: FindingKind,
+        row: usize,
+        channel: Option<&'static str>,
+        severity: Severity,
+        message: String,
+    ) {
+        self.findings.push(Finding {
+            kind,
+            row,
+            channel,
+            severity,
+            message,
+        });
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ValidationConfig {
+    /// Depth may drop by this much (ft) between samples before it is flagged.
+    pub depth_reversal_tolerance_ft: f64,
+    /// Identical non-zero readings in a row before a sensor counts as frozen.
+    pub frozen_run_length: usize,
+    /// Largest expected gap between samples, in seconds.
+    pub max_gap_seconds: f64,
+    /// Neighbours on each side used for the rolling median in spike detection.
+    pub spike_window: usize,
+    /// Deviation from the rolling median, in robust standard deviations,
+    /// above which a reading is a spike.
+    pub spike_threshold: f64,
+    /// Largest believable pitch change per ft of measured depth, in degrees.
+    pub max_pitch_rate_deg_per_ft: f64,
+}
+
+impl Default for ValidationConfig {
+    fn default() -> Self {
+        Self {
+            depth_reversal_tolerance_ft: 0.5,
+            frozen_run_length: 30,
+            max_gap_seconds: 10.0,
+            spike_window: 5,
+            spike_threshold: 8.0,
+            max_pitch_rate_deg_per_ft: 0.5,
+        }
+    }
+}
+
+type Accessor = fn(&TelemetryData) -> Option<f64>;
+
+const CHANNELS: [(&str, Accessor); 4] = [
+    ("torque", |d| d.torque),
+    ("thrust", |d| d.thrust),
+    ("mud_flow", |d| d.mud_flow),
+    ("bit_rpm", |d| d.bit_rpm),
+];
+
+/// Checks raw telemetry for placeholders, depth reversals, frozen sensors,
+/// spikes and timestamp gaps. Nothing is removed; the caller decides what
+/// to do with the findings.
+pub fn validate_telemetry(rows: &[TelemetryData], config: &ValidationConfig) -> ValidationReport {
+    let mut report = ValidationReport {
+        rows_checked: rows.len(),
+        findings: Vec::new(),
+    };
+
+    check_values(rows, &mut report);
+    check_depth(rows, config, &mut report);
+    check_timestamps(rows, config, &mut report);
+    for (name, get) in CHANNELS {
+        let values: Vec<Option<f64>> = rows.iter().map(get).map(usable).collect();
+        check_frozen(&values, name, config, &mut report);
+        check_spikes(&values, name, config, &mut report);
+    }
+
+    report.findings.sort_by_key(|f| f.row);
+    report
+}
+
+fn is_placeholder(value: f64) -> bool {
+    (value - DEFAULT_NULL_VALUE).abs() < 1e-9
+}
+
+/// Values that can take part in statistics.
+fn usable(value: Option<f64>) -> Option<f64> {
+    value.filter(|v| v.is_finite() && !is_placeholder(*v))
+}
+
+fn check_values(rows: &[TelemetryData], report: &mut ValidationReport) {
+    for (row, data) in rows.iter().enumerate() {
+        let depth = ("depth", Some(data.depth));
+        let channels = CHANNELS.iter().map(|(name, get)| (*name, get(data)));
+        for (name, value) in std::iter::once(depth).chain(channels) {
+            let Some(value) = value else { continue };
+            if is_placeholder(value) {
+                report.push(
+                    FindingKind::NullPlaceholder,
+                    row,
+                    Some(name),
+                    Severity::Warning,
+                    format!("{} holds the {} null placeholder", name, DEFAULT_NULL_VALUE),
+                );
+            } else if !value.is_finite() {
+                report.push(
+                    FindingKind::NonFinite,
+                    row,
+                    Some(name),
+                    Severity::Error,
+                    format!("{} is {}", name, value),
+                );
+            }
+        }
+    }
+}
+
+fn check_depth(rows: &[TelemetryData], config: &ValidationConfig, report: &mut ValidationReport) {
+    let mut previous: Option<f64> = None;
+    for (row, data) in rows.iter().enumerate() {
+        let Some(depth) = usable(Some(data.depth)) else {
+            continue;
+        };
+        if let Some(prev) = previous {
+            if prev - depth > config.depth_reversal_tolerance_ft {
+                report.push(
+                    FindingKind::DepthReversal,
+                    row,
+                    Some("depth"),
+                    Severity::Warning,
+                    format!("depth went back from {:.2} ft to {:.2} ft", prev, depth),
+                );
+            }
+        }
+        previous = Some(depth);
+    }
+}
+
+fn check_timestamps(
+    rows: &[TelemetryData],
+    config: &ValidationConfig,
+    report: &mut ValidationReport,
+) {
+    let mut previous: Option<DateTime<Utc>> = None;
+    for (row, data) in rows.iter().enumerate() {
+        if data.timestamp.is_empty() {
+            continue;
+        }
+        // Only gaps and order are checked, which don't depend on where
+        // plain date-times sit relative to UTC.
+        let Some(time) = parse_timestamp(&data.timestamp, NaiveTimes::Utc) else {
+            report.push(
+                FindingKind::UnparseableTimestamp,
+                row,
+                Some("timestamp"),
+                Severity::Info,
+                format!("'{}' is not a recognised timestamp", data.timestamp),
+            );
+            continue;
+        };
+        if let Some(prev) = previous {
+            let seconds = (time - prev).num_milliseconds() as f64 / 1000.0;
+            if seconds < 0.0 {
+                report.push(
+                    FindingKind::TimestampReversal,
+                    row,
+                    Some("timestamp"),
+                    Severity::Error,
+                    format!("timestamp is {:.1} s before the previous sample", -seconds),
+                );
+            } else if seconds > config.max_gap_seconds {
+                report.push(
+                    FindingKind::TimestampGap,
+                    row,
+                    Some("timestamp"),
+                    Severity::Warning,
+                    format!("{:.1} s gap since the previous sample", seconds),
+                );
+            }
+        }
+        previous = Some(time);
+    }
+}
+
+/// Reports each run of identical non-zero readings once, at its first row.
+fn check_frozen(
+    values: &[Option<f64>],
+    name: &'static str,
+    config: &ValidationConfig,
+    report: &mut ValidationReport,
+) {
+    let mut start = 0;
+    while start < values.len() {
+        let mut end = start + 1;
+        while end < values.len() && values[end].is_some() && values[end] == values[start] {
+            end += 1;
+        }
+        let run = end - start;
+        if let Some(value) = values[start] {
+            if value != 0.0 && config.frozen_run_length > 0 && run >= config.frozen_run_length {
+                report.push(
+                    FindingKind::FrozenSensor,
+                    start,
+                    Some(name),
+                    Severity::Warning,
+                    format!("{} stuck at {} for {} samples", name, value, run),
+                );
+            }
+        }
+        start = end;
+    }
+}
+
+fn median(values: &mut [f64]) -> f64 {
+    values.sort_by(f64::total_cmp);
+    let mid = values.len() / 2;
+    if values.len().is_multiple_of(2) {
+        (values[mid - 1] + values[mid]) / 2.0
+    } else {
+        values[mid]
+    }
+}
+
+/// Flags readings far from the median of their neighbours, using the median
+/// absolute deviation (MAD) as a robust spread.
+fn check_spikes(
+    values: &[Option<f64>],
+    name: &'static str,
+    config: &ValidationConfig,
+    report: &mut ValidationReport,
+) {
+    let k = config.spike_window;
+    if k == 0 {
+        return;
+    }
+    for i in 0..values.len() {
+        let Some(value) = values[i] else { continue };
+        let lo = i.saturating_sub(k);
+        let hi = (i + k + 1).min(values.len());
+        let mut neighbours: Vec<f64> = (lo..hi)
+            .filter(|&j| j != i)
+            .filter_map(|j| values[j])
+            .collect();
+        if neighbours.len() < k {
+            continue;
+        }
+        let centre = median(&mut neighbours);
+        let mut deviations: Vec<f64> = neighbours.iter().map(|v| (v - centre).abs()).collect();
+        let spread = (1.4826 * median(&mut deviations))
+            .max(0.01 * centre.abs())
+            .max(1e-9);
+        let score = (value - centre).abs() / spread;
+        if score > config.spike_threshold {
+            report.push(
+                FindingKind::Spike,
+                i,
+                Some(name),
+                Severity::Warning,
+                format!(
+                    "{} of {} is far from the local median {}",
+                    name, value, centre
+                ),
+            );
+        }
+    }
+}
+
+/// Checks survey stations for duplicate or decreasing MDs, out-of-range
+/// pitch and pitch changes faster than the pipe can bend.
+pub fn validate_surveys(surveys: &[SurveyPoint], config: &ValidationConfig) -> ValidationReport {
+    let mut report = ValidationReport {
+        rows_checked: surveys.len(),
+        findings: Vec::new(),
+    };
+
+    for (row, station) in surveys.iter().enumerate() {
+        if !station.inclination.is_finite() || station.inclination.abs() > 90.0 {
+            report.push(
+                FindingKind::PitchOutOfRange,
+                row,
+                Some("pitch"),
+                Severity::Error,
+                format!("pitch {} is outside -90..90 degrees", station.inclination),
+            );
+        }
+        let Some(prev) = row.checked_sub(1).map(|p| &surveys[p]) else {
+            continue;
+        };
+
+        let d_md = station.measured_depth - prev.measured_depth;
+        if d_md.abs() < 1e-6 {
+            report.push(
+                FindingKind::DuplicateMeasuredDepth,
+                row,
+                Some("measured_depth"),
+                Severity::Error,
+                format!(
+                    "MD {:.2} ft repeats the previous station",
+                    station.measured_depth
+                ),
+            );
+            continue;
+        }
+        if d_md < 0.0 {
+            report.push(
+                FindingKind::MeasuredDepthDecrease,
+                row,
+                Some("measured_depth"),
+                Severity::Error,
+                format!(
+                    "MD drops from {:.2} ft to {:.2} ft",
+                    prev.measured_depth, station.measured_depth
+                ),
+            );
+            continue;
+        }
+
+        let rate = (station.inclination - prev.inclination).abs() / d_md;
+        if rate > config.max_pitch_rate_deg_per_ft {
+            report.push(
+                FindingKind::PitchJump,
+                row,
+                Some("pitch"),
+                Severity::Error,
+                format!(
+                    "pitch changes {:.2} deg over {:.1} ft ({:.2} deg/ft)",
+                    station.inclination - prev.inclination,
+                    d_md,
+                    rate
+                ),
+            );
+        }
+    }
+
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample(i: usize, depth: f64, torque: f64) -> TelemetryData {
+        TelemetryData {
+            timestamp: format!("2024-05-01T10:{:02}:{:02}Z", i / 60, i % 60),
+            depth,
+            torque: Some(torque),
+            thrust: Some(10_000.0 + (i % 7) as f64 * 50.0),
+            mud_flow: None,
+            bit_rpm: None,
+        }
+    }
+
+    #[test]
+    fn test_telemetry_findings() {
+        let mut rows: Vec<TelemetryData> = (0..40)
+            .map(|i| sample(i, i as f64 * 0.5, 1500.0 + (i % 5) as f64 * 10.0))
+            .collect();
+        rows[10].torque = Some(9000.0);
+        rows[20].depth = 2.0;
+        rows[25].thrust = Some(DEFAULT_NULL_VALUE);
+        rows[30].timestamp = "2024-05-01T10:05:00Z".into();
+
+        let report = validate_telemetry(&rows, &ValidationConfig::default());
+        let kinds: Vec<(FindingKind, usize)> =
+            report.findings.iter().map(|f| (f.kind, f.row)).collect();
+        assert!(kinds.contains(&(FindingKind::Spike, 10)));
+        assert!(kinds.contains(&(FindingKind::DepthReversal, 20)));
+        assert!(kinds.contains(&(FindingKind::NullPlaceholder, 25)));
+        assert!(kinds.contains(&(FindingKind::TimestampGap, 30)));
+        assert!(kinds.contains(&(FindingKind::TimestampReversal, 31)));
+        assert!(!kinds.iter().any(|(k, _)| *k == FindingKind::FrozenSensor));
+
+        // Plain date-times from CSV and WITS0 imports are checked, not flagged.
+        for row in &mut rows {
+            row.timestamp = row.timestamp.trim_end_matches('Z').replacen('T', " ", 1);
+        }
+        let report = validate_telemetry(&rows, &ValidationConfig::default());
+        let kinds: Vec<(FindingKind, usize)> =
+            report.findings.iter().map(|f| (f.kind, f.row)).collect();
+        assert!(!kinds
+            .iter()
+            .any(|(k, _)| *k == FindingKind::UnparseableTimestamp));
+        assert!(kinds.contains(&(FindingKind::TimestampReversal, 31)));
+    }
+
+    #[test]
+    fn test_frozen_sensor() {
+        let rows: Vec<TelemetryData> = (0..40).map(|i| sample(i, i as f64, 1500.0)).collect();
+        let report = validate_telemetry(&rows, &ValidationConfig::default());
+        let frozen: Vec<&Finding> = report
+            .findings
+            .iter()
+            .filter(|f| f.kind == FindingKind::FrozenSensor)
+            .collect();
+        assert_eq!(frozen.len(), 1);
+        assert_eq!(frozen[0].channel, Some("torque"));
+    }
+
+    #[test]
+    fn test_survey_findings() {
+        let surveys = vec![
+            SurveyPoint {
+                measured_depth: 0.0,
+                inclination: 12.0,
+                azimuth: 90.0,
+            },
+            SurveyPoint {
+                measured_depth: 10.0,
+                inclination: 10.0,
+                azimuth: 90.0,
+            },
+            SurveyPoint {
+                measured_depth: 10.0,
+                inclination: 10.0,
+                azimuth: 90.0,
+            },
+            SurveyPoint {
+                measured_depth: 20.0,
+                inclination: -2.0,
+                azimuth: 90.0,
+            },
+        ];
+        let report = validate_surveys(&surveys, &ValidationConfig::default());
+        assert_eq!(report.findings.len(), 2);
+        assert_eq!(report.findings[0].kind, FindingKind::DuplicateMeasuredDepth);
+        assert_eq!(report.findings[1].kind, FindingKind::PitchJump);
+        assert!(report.has_errors());
+    }
+}
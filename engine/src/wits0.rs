@@ -0,0 +1,469 @@
+use crate::mnemonics::Channel;
+use crate::uom::{self, Unit};
+use crate::witsml_parser::{TelemetryData, WitsmlError};
+use chrono::{FixedOffset, NaiveDateTime};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use thiserror::Error;
+use tokio::io::{AsyncBufRead, AsyncBufReadExt, AsyncReadExt, BufReader};
+use tokio::net::TcpListener;
+use tokio::sync::mpsc;
+
+const RECORD_START: &str = "&&";
+const RECORD_END: &str = "!!";
+/// Longest line accepted from a feed; real WITS0 items are a few dozen bytes.
+const MAX_LINE_BYTES: usize = 1024;
+
+#[derive(Error, Debug)]
+pub enum Wits0Error {
+    #[error("WITS0 item map error: {0}")]
+    Config(String),
+    #[error(transparent)]
+    Unit(#[from] WitsmlError),
+    #[error("Data outside a WITS0 record: '{0}'")]
+    OutsideRecord(String),
+    #[error("WITS0 record started before the previous one was closed")]
+    UnterminatedRecord,
+    #[error("Malformed WITS0 item line '{0}'")]
+    InvalidItem(String),
+    #[error("Item {item}: cannot parse '{value}'")]
+    InvalidValue { item: String, value: String },
+    #[error("WITS0 record has no depth item")]
+    MissingDepth,
+    #[error("WITS0 line longer than {0} bytes")]
+    LineTooLong(usize),
+    #[error("IO Error: {0}")]
+    Io(#[from] std::io::Error),
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct Wits0Item {
+    pub channel: Channel,
+    /// Unit the rig sends the item in; empty means canonical.
+    #[serde(default)]
+    pub unit: String,
+}
+
+/// Which WITS0 items feed which `TelemetryData` fields.
+///
+/// Item codes are the four-digit record + item numbers, e.g. `0108` for bit
+/// depth in record 1. Files look like:
+///
+/// ```toml
+/// date_item = "0105"
+/// time_item = "0106"
+/// utc_offset = "-05:00"
+///
+/// [items]
+/// "0108" = { channel = "depth", unit = "ft" }
+/// "0118" = { channel = "torque", unit = "kft.lbf" }
+/// ```
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct Wits0Map {
+    /// `YYMMDD` date item used to build the sample timestamp.
+    #[serde(default)]
+    pub date_item: Option<String>,
+    /// `HHMMSS` time item used to build the sample timestamp.
+    #[serde(default)]
+    pub time_item: Option<String>,
+    /// Offset of the rig clock from UTC, e.g. `-05:00`. Without it the
+    /// date/time items are passed on as local time, with no zone suffix,
+    /// for downstream code to place with a
+    /// [`NaiveTimes`](crate::timestamp::NaiveTimes) policy.
+    #[serde(default)]
+    pub utc_offset: Option<String>,
+    #[serde(default)]
+    pub items: BTreeMap<String, Wits0Item>,
+}
+
+impl Default for Wits0Map {
+    /// WITS record 1 (general time-based) in the usual imperial units.
+    fn default() -> Self {
+        let item = |channel, unit: &str| Wits0Item {
+            channel,
+            unit: unit.to_string(),
+        };
+        Self {
+            date_item: Some("0105".into()),
+            time_item: Some("0106".into()),
+            utc_offset: None,
+            items: BTreeMap::from([
+                ("0108".into(), item(Channel::Depth, "ft")),
+                ("0116".into(), item(Channel::Thrust, "klbf")),
+                ("0118".into(), item(Channel::Torque, "kft.lbf")),
+                ("0120".into(), item(Channel::BitRpm, "rpm")),
+                ("0131".into(), item(Channel::MudFlow, "gal/min")),
+            ]),
+        }
+    }
+}
+
+impl Wits0Map {
+    pub fn from_toml_str(s: &str) -> Result<Self, Wits0Error> {
+        toml::from_str(s).map_err(|e| Wits0Error::Config(e.to_string()))
+    }
+}
+
+fn is_item_code(code: &str) -> bool {
+    code.len() == 4 && code.bytes().all(|b| b.is_ascii_digit())
+}
+
+/// Turns WITS0 lines into telemetry samples, one per `&&` ... `!!` record.
+///
+/// Framing errors are reported for the offending line or record and the
+/// decoder resynchronises on the next `&&`, so a live feed keeps flowing
+/// after a garbled transmission.
+#[derive(Debug, Clone)]
+pub struct Wits0Decoder {
+    date_item: Option<String>,
+    time_item: Option<String>,
+    utc_offset: Option<FixedOffset>,
+    items: BTreeMap<String, (Channel, Option<Unit>)>,
+    record: Option<Vec<(String, String)>>,
+}
+
+impl Wits0Decoder {
+    pub fn new(map: &Wits0Map) -> Result<Self, Wits0Error> {
+        let mut items = BTreeMap::new();
+        let mut codes = map.items.keys().chain(&map.date_item).chain(&map.time_item);
+        if let Some(code) = codes.find(|c| !is_item_code(c)) {
+            return Err(Wits0Error::Config(format!(
+                "'{}' is not a four-digit item code",
+                code
+            )));
+        }
+        let utc_offset = match &map.utc_offset {
+            Some(offset) => Some(offset.parse::<FixedOffset>().map_err(|_| {
+                Wits0Error::Config(format!("'{}' is not a UTC offset like -05:00", offset))
+            })?),
+            None => None,
+        };
+        for (code, item) in &map.items {
+            let unit = match item.channel.quantity() {
+                Some(quantity) => Some(uom::lookup_for(&item.unit, quantity)?),
+                None => None,
+            };
+            items.insert(code.clone(), (item.channel, unit));
+        }
+        Ok(Self {
+            date_item: map.date_item.clone(),
+            time_item: map.time_item.clone(),
+            utc_offset,
+            items,
+            record: None,
+        })
+    }
+
+    /// Feeds one line; returns a sample or error when a record closes or the
+    /// framing breaks.
+    pub fn push_line(&mut self, line: &str) -> Option<Result<TelemetryData, Wits0Error>> {
+        let line = line.trim();
+        if line.is_empty() {
+            return None;
+        }
+        match line {
+            RECORD_START => self
+                .record
+                .replace(Vec::new())
+                .map(|_| Err(Wits0Error::UnterminatedRecord)),
+            RECORD_END => match self.record.take() {
+                Some(items) => Some(self.build(&items)),
+                None => Some(Err(Wits0Error::OutsideRecord(line.to_string()))),
+            },
+            _ => {
+                let Some(record) = self.record.as_mut() else {
+                    return Some(Err(Wits0Error::OutsideRecord(line.to_string())));
+                };
+                match line.get(..4).filter(|code| is_item_code(code)) {
+                    Some(code) => {
+                        record.push((code.to_string(), line[4..].trim().to_string()));
+                        None
+                    }
+                    None => {
+                        // A garbled item poisons the whole record.
+                        self.record = None;
+                        Some(Err(Wits0Error::InvalidItem(line.to_string())))
+                    }
+                }
+            }
+        }
+    }
+
+    /// Decodes every record in a block of text.
+    pub fn decode_str(&mut self, text: &str) -> Vec<Result<TelemetryData, Wits0Error>> {
+        text.lines().filter_map(|l| self.push_line(l)).collect()
+    }
+
+    fn build(&self, items: &[(String, String)]) -> Result<TelemetryData, Wits0Error> {
+        let mut row = TelemetryData {
+            timestamp: String::new(),
+            depth: f64::NAN,
+            torque: None,
+            thrust: None,
+            mud_flow: None,
+            bit_rpm: None,
+        };
+        let value_of = |code: &Option<String>| {
+            code.as_ref()
+                .and_then(|c| items.iter().find(|(i, _)| i == c))
+                .map(|(_, v)| v.as_str())
+        };
+        if let (Some(date), Some(time)) = (value_of(&self.date_item), value_of(&self.time_item)) {
+            let stamp = format!("{}{:0>6}", date, time);
+            let parsed = NaiveDateTime::parse_from_str(&stamp, "%y%m%d%H%M%S").map_err(|_| {
+                Wits0Error::InvalidValue {
+                    item: self.date_item.clone().unwrap_or_default(),
+                    value: stamp.clone(),
+                }
+            })?;
+            row.timestamp = match self.utc_offset {
+                Some(offset) => (parsed - offset).format("%Y-%m-%dT%H:%M:%SZ").to_string(),
+                None => parsed.format("%Y-%m-%dT%H:%M:%S").to_string(),
+            };
+        }
+
+        for (code, raw) in items {
+            let Some((channel, unit)) = self.items.get(code) else {
+                continue;
+            };
+            if *channel == Channel::Timestamp {
+                row.timestamp = raw.clone();
+                continue;
+            }
+            let value: f64 = raw.parse().map_err(|_| Wits0Error::InvalidValue {
+                item: code.clone(),
+                value: raw.clone(),
+            })?;
+            let value = unit.as_ref().map_or(value, |u| u.to_canonical(value));
+            match channel {
+                Channel::Depth => row.depth = value,
+                Channel::Torque => row.torque = Some(value),
+                Channel::Thrust => row.thrust = Some(value),
+                Channel::MudFlow => row.mud_flow = Some(value),
+                Channel::BitRpm => row.bit_rpm = Some(value),
+                Channel::Timestamp => {}
+            }
+        }
+        if row.depth.is_nan() {
+            return Err(Wits0Error::MissingDepth);
+        }
+        Ok(row)
+    }
+}
+
+/// Splits a feed into lines without buffering more than `MAX_LINE_BYTES`.
+struct LineReader<R> {
+    reader: R,
+    buf: Vec<u8>,
+    /// Skipping the rest of an overlong line.
+    discarding: bool,
+}
+
+impl<R: AsyncBufRead + Unpin> LineReader<R> {
+    fn new(reader: R) -> Self {
+        Self {
+            reader,
+            buf: Vec::new(),
+            discarding: false,
+        }
+    }
+
+    /// Next line, `Ok(None)` at the end of the feed, or `LineTooLong` once
+    /// per overlong line, whose remainder is then skipped. Safe to cancel
+    /// and call again.
+    async fn next_line(&mut self) -> Result<Option<String>, Wits0Error> {
+        loop {
+            let room = (MAX_LINE_BYTES + 1).saturating_sub(self.buf.len()) as u64;
+            let read = (&mut self.reader)
+                .take(room)
+                .read_until(b'\n', &mut self.buf)
+                .await?;
+            let complete = self.buf.last() == Some(&b'\n');
+            if self.discarding {
+                self.buf.clear();
+                if complete {
+                    self.discarding = false;
+                } else if read == 0 {
+                    return Ok(None);
+                }
+                continue;
+            }
+            if !complete && self.buf.len() > MAX_LINE_BYTES {
+                self.buf.clear();
+                self.discarding = true;
+                return Err(Wits0Error::LineTooLong(MAX_LINE_BYTES));
+            }
+            if complete || (read == 0 && !self.buf.is_empty()) {
+                let line = String::from_utf8_lossy(&self.buf).into_owned();
+                self.buf.clear();
+                return Ok(Some(line));
+            }
+            if read == 0 {
+                return Ok(None);
+            }
+        }
+    }
+}
+
+/// Accepts WITS0 feeds on `listener` and sends decoded samples to `tx`.
+///
+/// Each connection gets its own decoder. Bad records, overlong lines and
+/// read errors are sent on as `Err`; a read error also closes that
+/// connection. The loop ends when the receiver is dropped.
+pub async fn serve_wits0(
+    listener: TcpListener,
+    map: Wits0Map,
+    tx: mpsc::Sender<Result<TelemetryData, Wits0Error>>,
+) -> Result<(), Wits0Error> {
+    let decoder = Wits0Decoder::new(&map)?;
+    loop {
+        let socket = tokio::select! {
+            _ = tx.closed() => return Ok(()),
+            accepted = listener.accept() => accepted?.0,
+        };
+        let mut decoder = decoder.clone();
+        let tx = tx.clone();
+        tokio::spawn(async move {
+            let mut lines = LineReader::new(BufReader::new(socket));
+            loop {
+                let line = tokio::select! {
+                    _ = tx.closed() => break,
+                    line = lines.next_line() => line,
+                };
+                let (result, more) = match line {
+                    Ok(Some(line)) => match decoder.push_line(&line) {
+                        Some(result) => (result, true),
+                        None => continue,
+                    },
+                    Ok(None) => break,
+                    Err(Wits0Error::LineTooLong(max)) => {
+                        // Like a garbled item, a cut-off line spoils its record.
+                        decoder.record = None;
+                        (Err(Wits0Error::LineTooLong(max)), true)
+                    }
+                    Err(e) => (Err(e), false),
+                };
+                if tx.send(result).await.is_err() || !more {
+                    break;
+                }
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::io::AsyncWriteExt;
+    use tokio::net::TcpStream;
+
+    const RECORD: &str =
+        "&&\r\n0105240501\r\n0106100000\r\n0108152.5\r\n0116 8.2\r\n0118 1.5\r\n0120 62\r\n!!\r\n";
+
+    #[test]
+    fn test_decode_record() {
+        let mut decoder = Wits0Decoder::new(&Wits0Map::default()).unwrap();
+        let rows = decoder.decode_str(RECORD);
+        let row = rows[0].as_ref().unwrap();
+        assert_eq!(row.timestamp, "2024-05-01T10:00:00");
+        assert_eq!(row.depth, 152.5);
+        assert!((row.thrust.unwrap() - 8200.0).abs() < 1e-9);
+        assert!((row.torque.unwrap() - 1500.0).abs() < 1e-9);
+        assert_eq!(row.bit_rpm, Some(62.0));
+        assert_eq!(row.mud_flow, None);
+
+        // A rig clock on UTC-5 is converted to UTC.
+        let map = Wits0Map {
+            utc_offset: Some("-05:00".into()),
+            ..Wits0Map::default()
+        };
+        let rows = Wits0Decoder::new(&map).unwrap().decode_str(RECORD);
+        assert_eq!(rows[0].as_ref().unwrap().timestamp, "2024-05-01T15:00:00Z");
+    }
+
+    #[test]
+    fn test_framing_errors_resync() {
+        let mut decoder = Wits0Decoder::new(&Wits0Map::default()).unwrap();
+        let text = format!(
+            "0108 10\n&&\n0108 11\n&&\n01X8 12\n!!\n&&\n0120 5\n!!\n{}",
+            RECORD
+        );
+        let results = decoder.decode_str(&text);
+        assert!(matches!(results[0], Err(Wits0Error::OutsideRecord(_))));
+        assert!(matches!(results[1], Err(Wits0Error::UnterminatedRecord)));
+        assert!(matches!(results[2], Err(Wits0Error::InvalidItem(_))));
+        assert!(matches!(results[3], Err(Wits0Error::OutsideRecord(_))));
+        assert!(matches!(results[4], Err(Wits0Error::MissingDepth)));
+        assert_eq!(results[5].as_ref().unwrap().depth, 152.5);
+    }
+
+    #[test]
+    fn test_custom_map() {
+        let map = Wits0Map::from_toml_str(
+            r#"
+            [items]
+            "0110" = { channel = "depth", unit = "m" }
+            "0131" = { channel = "mud_flow", unit = "L/min" }
+            "#,
+        )
+        .unwrap();
+        let mut decoder = Wits0Decoder::new(&map).unwrap();
+        let rows = decoder.decode_str("&&\n0110 100\n0131 1000\n!!\n");
+        let row = rows[0].as_ref().unwrap();
+        assert!((row.depth - 328.0839).abs() < 1e-3);
+        assert!((row.mud_flow.unwrap() - 264.172).abs() < 1e-3);
+        assert_eq!(row.timestamp, "");
+
+        let bad = Wits0Map::from_toml_str("[items]\n\"108\" = { channel = \"depth\" }").unwrap();
+        assert!(matches!(
+            Wits0Decoder::new(&bad),
+            Err(Wits0Error::Config(_))
+        ));
+        let bad = Wits0Map::from_toml_str("utc_offset = \"EST\"").unwrap();
+        assert!(matches!(
+            Wits0Decoder::new(&bad),
+            Err(Wits0Error::Config(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_listener_with_simulator() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let (tx, mut rx) = mpsc::channel(16);
+        let server = tokio::spawn(serve_wits0(listener, Wits0Map::default(), tx));
+
+        // Simulated rig data unit: a garbled record, then two good ones.
+        let mut rig = TcpStream::connect(addr).await.unwrap();
+        rig.write_all(b"&&\r\n0108 1\r\n&&\r\n").await.unwrap();
+        rig.write_all(b"0108 20\r\n!!\r\n").await.unwrap();
+        rig.write_all(RECORD.as_bytes()).await.unwrap();
+        // A line that never ends within the limit is cut off and reported.
+        rig.write_all(b"&&\r\n0108 3").await.unwrap();
+        rig.write_all(&[b'0'; 4 * MAX_LINE_BYTES]).await.unwrap();
+        rig.write_all(b"\r\n!!\r\n").await.unwrap();
+        rig.write_all(RECORD.as_bytes()).await.unwrap();
+        rig.shutdown().await.unwrap();
+
+        assert!(matches!(
+            rx.recv().await.unwrap(),
+            Err(Wits0Error::UnterminatedRecord)
+        ));
+        assert_eq!(rx.recv().await.unwrap().unwrap().depth, 20.0);
+        assert_eq!(rx.recv().await.unwrap().unwrap().depth, 152.5);
+        assert!(matches!(
+            rx.recv().await.unwrap(),
+            Err(Wits0Error::LineTooLong(MAX_LINE_BYTES))
+        ));
+        assert!(matches!(
+            rx.recv().await.unwrap(),
+            Err(Wits0Error::OutsideRecord(line)) if line == "!!"
+        ));
+        assert_eq!(rx.recv().await.unwrap().unwrap().depth, 152.5);
+
+        // Dropping the receiver stops the listener without another connection.
+        drop(rx);
+        let stopped = tokio::time::timeout(std::time::Duration::from_secs(5), server).await;
+        assert!(matches!(stopped, Ok(Ok(Ok(())))));
+    }
+}
@@ -1,16 +1,60 @@
+use crate::mnemonics::{Channel, MnemonicMap};
+use crate::uom::{self, Quantity, Unit};
+use crate::SurveyPoint;
 use quick_xml::de::from_str;
+use quick_xml::events::Event;
+use quick_xml::Reader;
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
+/// Conventional WITSML/LAS placeholder for "no reading".
+pub(crate) const DEFAULT_NULL_VALUE: f64 = -999.25;
+
 #[derive(Error, Debug)]
 pub enum WitsmlError {
     #[error("XML Parsing Error: {0}")]
     XmlError(#[from] quick_xml::DeError),
+    #[error("XML Reader Error: {0}")]
+    ReaderError(#[from] quick_xml::Error),
     #[error("Data Conversion Error")]
     ConversionError,
+    #[error("Log has no curve mapped to depth")]
+    MissingDepthCurve,
+    #[error("Row {row}: expected {expected} columns, found {found}")]
+    ColumnCount {
+        row: usize,
+        expected: usize,
+        found: usize,
+    },
+    #[error("Row {row}, column {column}: cannot parse '{value}'")]
+    InvalidValue {
+        row: usize,
+        column: String,
+        value: String,
+    },
+    #[error("Row {row}: depth is null")]
+    NullDepth { row: usize },
+    #[error("Unknown unit of measure '{0}'")]
+    UnknownUnit(String),
+    #[error("Unit '{unit}' is not a unit of {expected}")]
+    IncompatibleUnit { unit: String, expected: Quantity },
+    #[error("Mnemonic alias config error: {0}")]
+    AliasConfig(String),
+    #[error("IO Error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Log has no uid")]
+    MissingUid,
+    #[error("Unsupported log index type '{0}'")]
+    UnsupportedIndexType(String),
+    #[error("Log '{uid}' is not indexed by {expected}")]
+    IndexMismatch { uid: String, expected: LogIndexType },
+    #[error("Expected a <log> or <logs> document, found <{0}>")]
+    UnexpectedRoot(String),
 }
 
-#[derive(Debug, Serialize, Deserialize, PartialEq)]
+/// One telemetry sample in canonical units: depth in ft, torque in ft·lbf,
+/// thrust in lbf, mud flow in gal/min and bit speed in rpm.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct TelemetryData {
     pub timestamp: String,
     pub depth: f64,
@@ -23,50 +67,519 @@ pub struct TelemetryData {
 // Simplified WITSML Log Structure
 #[derive(Debug, Deserialize)]
 #[serde(rename_all = "camelCase")]
-#[allow(dead_code)]
 struct WitsmlLog {
+    #[serde(rename = "@uid")]
+    uid: Option<String>,
+    #[serde(rename = "@uidWell")]
+    uid_well: Option<String>,
+    #[serde(rename = "@uidWellbore")]
+    uid_wellbore: Option<String>,
+    #[serde(default)]
+    index_type: Option<String>,
+    #[serde(default)]
+    null_value: Option<String>,
     log_curve_info: Vec<LogCurveInfo>,
     log_data: LogData,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Default, Deserialize)]
 #[serde(rename_all = "camelCase")]
-#[allow(dead_code)]
-struct LogCurveInfo {
-    mnemonic: String,
-    unit: String,
+pub(crate) struct LogCurveInfo {
+    pub(crate) mnemonic: String,
+    #[serde(default)]
+    pub(crate) unit: String,
+    #[serde(default)]
+    pub(crate) null_value: Option<String>,
 }
 
 #[derive(Debug, Deserialize)]
 #[serde(rename_all = "camelCase")]
-#[allow(dead_code)]
 struct LogData {
+    #[serde(default)]
     data: Vec<String>,
 }
 
+#[derive(Debug, Deserialize)]
+struct WitsmlLogs {
+    #[serde(default)]
+    log: Vec<WitsmlLog>,
+}
+
+/// How a log's rows are indexed (`<indexType>`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LogIndexType {
+    MeasuredDepth,
+    DateTime,
+}
+
+impl LogIndexType {
+    /// Parses a WITSML `indexType`; a missing value means measured depth.
+    fn from_witsml(index_type: Option<&str>) -> Result<Self, WitsmlError> {
+        match index_type.map(str::trim) {
+            None | Some("measured depth") => Ok(LogIndexType::MeasuredDepth),
+            Some("date time") => Ok(LogIndexType::DateTime),
+            Some(other) => Err(WitsmlError::UnsupportedIndexType(other.to_string())),
+        }
+    }
+}
+
+impl std::fmt::Display for LogIndexType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LogIndexType::MeasuredDepth => f.write_str("measured depth"),
+            LogIndexType::DateTime => f.write_str("date time"),
+        }
+    }
+}
+
+/// One transmission of a (possibly growing) WITSML log object.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LogFragment {
+    pub uid: Option<String>,
+    pub uid_well: Option<String>,
+    pub uid_wellbore: Option<String>,
+    pub index_type: LogIndexType,
+    pub rows: Vec<TelemetryData>,
+}
+
+/// Column layout of a log, resolved once from `logCurveInfo`.
+pub(crate) struct Column {
+    mnemonic: String,
+    channel: Option<Channel>,
+    unit: Option<Unit>,
+    null_value: Option<String>,
+}
+
 /// Parses a raw WITSML XML string into a vector of TelemetryData.
 ///
-/// Note: WITSML 1.3.1/1.4.1 stores data as comma-separated strings in the <data> tag.
-/// This parser assumes a simplified structure for demonstration.
-/// Real WITSML parsing requires mapping mnemonics (e.g., "TRQ", "WOB") to fields.
+/// WITSML 1.3.1/1.4.1 stores data as comma-separated strings in the <data> tag,
+/// ordered like the `logCurveInfo` entries. The document may be a single
+/// `<log>` or a `<logs>` container, whose logs are concatenated. Curves are mapped onto fields by
+/// mnemonic (e.g. "DEPT", "TRQ", "WOB") using the built-in vendor aliases;
+/// unknown curves are skipped. Values are converted from each curve's `unit`
+/// into canonical units.
 pub fn parse_witsml(xml: &str) -> Result<Vec<TelemetryData>, WitsmlError> {
-    // In a real implementation, we would parse the full XML.
-    // For this prototype, we'll simulate parsing logic or use a simplified XML structure.
+    parse_witsml_with(xml, &MnemonicMap::default())
+}
 
-    // Placeholder logic:
-    // 1. Parse XML to struct
-    // 2. Extract mnemonics order
-    // 3. Parse data rows
+/// Like `parse_witsml`, but maps curves through a caller-supplied alias table.
+pub fn parse_witsml_with(
+    xml: &str,
+    mnemonics: &MnemonicMap,
+) -> Result<Vec<TelemetryData>, WitsmlError> {
+    let mut rows = Vec::new();
+    for log in read_logs(xml)? {
+        let columns = resolve_columns(log.log_curve_info, log.null_value.as_deref(), mnemonics)?;
+        for (row, line) in log.log_data.data.iter().enumerate() {
+            rows.push(parse_row(line, row, &columns)?);
+        }
+    }
+    Ok(rows)
+}
 
-    // For now, let's just return an empty vec or mock data if the XML is valid
-    let _log: WitsmlLog = from_str(xml)?;
+/// Local name of the document's root element, or `None` if it has none.
+fn root_element(xml: &str) -> Result<Option<String>, WitsmlError> {
+    let mut reader = Reader::from_str(xml);
+    loop {
+        match reader.read_event()? {
+            Event::Start(e) | Event::Empty(e) => {
+                return Ok(Some(
+                    String::from_utf8_lossy(e.local_name().as_ref()).into_owned(),
+                ))
+            }
+            Event::Eof => return Ok(None),
+            _ => {}
+        }
+    }
+}
+
+/// Deserializes either a bare `<log>` or every `<log>` in a `<logs>` container.
+fn read_logs(xml: &str) -> Result<Vec<WitsmlLog>, WitsmlError> {
+    match root_element(xml)?.as_deref() {
+        Some("logs") => Ok(from_str::<WitsmlLogs>(xml)?.log),
+        Some("log") | None => Ok(vec![from_str::<WitsmlLog>(xml)?]),
+        Some(other) => Err(WitsmlError::UnexpectedRoot(other.to_string())),
+    }
+}
+
+/// Parses every `<log>` in a document, keeping its identity and index type.
+///
+/// Real-time WITSML stores usually send each fragment in a `<logs>` container.
+pub fn parse_log_fragments(
+    xml: &str,
+    mnemonics: &MnemonicMap,
+) -> Result<Vec<LogFragment>, WitsmlError> {
+    read_logs(xml)?
+        .into_iter()
+        .map(|log| {
+            let index_type = LogIndexType::from_witsml(log.index_type.as_deref())?;
+            let columns =
+                resolve_columns(log.log_curve_info, log.null_value.as_deref(), mnemonics)?;
+            let rows = log
+                .log_data
+                .data
+                .iter()
+                .enumerate()
+                .map(|(row, line)| parse_row(line, row, &columns))
+                .collect::<Result<_, _>>()?;
+            Ok(LogFragment {
+                uid: log.uid,
+                uid_well: log.uid_well,
+                uid_wellbore: log.uid_wellbore,
+                index_type,
+                rows,
+            })
+        })
+        .collect()
+}
+
+/// Maps curve definitions onto telemetry fields and resolves their units.
+///
+/// Fails if no curve maps to depth, since every sample needs one.
+pub(crate) fn resolve_columns(
+    curves: Vec<LogCurveInfo>,
+    log_null_value: Option<&str>,
+    mnemonics: &MnemonicMap,
+) -> Result<Vec<Column>, WitsmlError> {
+    let columns: Vec<Column> = curves
+        .into_iter()
+        .map(|info| {
+            let channel = mnemonics.resolve(&info.mnemonic);
+            let unit = channel
+                .and_then(Channel::quantity)
+                .map(|q| uom::lookup_for(&info.unit, q))
+                .transpose()?;
+            Ok(Column {
+                channel,
+                unit,
+                null_value: info
+                    .null_value
+                    .or_else(|| log_null_value.map(str::to_string)),
+                mnemonic: info.mnemonic,
+            })
+        })
+        .collect::<Result<_, WitsmlError>>()?;
+
+    if !columns.iter().any(|c| c.channel == Some(Channel::Depth)) {
+        return Err(WitsmlError::MissingDepthCurve);
+    }
+    Ok(columns)
+}
 
-    Ok(vec![])
+/// Helper to parse a single comma-separated `<data>` row.
+pub(crate) fn parse_row(
+    line: &str,
+    row: usize,
+    columns: &[Column],
+) -> Result<TelemetryData, WitsmlError> {
+    let cells: Vec<&str> = line.split(',').map(str::trim).collect();
+    parse_cells(&cells, row, columns)
 }
 
-/// Helper to parse a single data row based on mnemonic indices
-#[allow(dead_code)]
-fn parse_row(_row: &str, _mnemonics: &[String]) -> Option<TelemetryData> {
-    // Implementation would go here
-    None
+/// Parses one row of already-split cells based on the column layout.
+pub(crate) fn parse_cells(
+    cells: &[&str],
+    row: usize,
+    columns: &[Column],
+) -> Result<TelemetryData, WitsmlError> {
+    if cells.len() != columns.len() {
+        return Err(WitsmlError::ColumnCount {
+            row,
+            expected: columns.len(),
+            found: cells.len(),
+        });
+    }
+
+    let mut data = TelemetryData {
+        timestamp: String::new(),
+        depth: f64::NAN,
+        torque: None,
+        thrust: None,
+        mud_flow: None,
+        bit_rpm: None,
+    };
+
+    for (column, &cell) in columns.iter().zip(cells) {
+        let Some(channel) = column.channel else {
+            continue;
+        };
+        if channel == Channel::Timestamp {
+            if !matches!(parse_value(cell, column.null_value.as_deref()), Ok(None)) {
+                data.timestamp = cell.to_string();
+            }
+            continue;
+        }
+
+        let value = parse_value(cell, column.null_value.as_deref()).map_err(|_| {
+            WitsmlError::InvalidValue {
+                row,
+                column: column.mnemonic.clone(),
+                value: cell.to_string(),
+            }
+        })?;
+        let value = match column.unit {
+            Some(unit) => value.map(|v| unit.to_canonical(v)),
+            None => value,
+        };
+
+        match channel {
+            Channel::Depth => data.depth = value.ok_or(WitsmlError::NullDepth { row })?,
+            Channel::Torque => data.torque = value,
+            Channel::Thrust => data.thrust = value,
+            Channel::MudFlow => data.mud_flow = value,
+            Channel::BitRpm => data.bit_rpm = value,
+            Channel::Timestamp => unreachable!(),
+        }
+    }
+
+    Ok(data)
+}
+
+/// Parses a numeric cell, treating empty cells and null placeholders as `None`.
+fn parse_value(
+    cell: &str,
+    null_value: Option<&str>,
+) -> Result<Option<f64>, std::num::ParseFloatError> {
+    if cell.is_empty() || Some(cell) == null_value {
+        return Ok(None);
+    }
+    let value: f64 = cell.parse()?;
+    let declared_null = null_value.and_then(|n| n.parse::<f64>().ok());
+    if value == DEFAULT_NULL_VALUE || Some(value) == declared_null {
+        return Ok(None);
+    }
+    Ok(Some(value))
+}
+
+/// A WITSML trajectory with its stations converted to HDD survey points.
+///
+/// Stations are sorted by measured depth so they can be passed straight to
+/// `calculate_path`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Trajectory {
+    pub uid: Option<String>,
+    pub uid_well: Option<String>,
+    pub uid_wellbore: Option<String>,
+    pub name: Option<String>,
+    pub stations: Vec<SurveyPoint>,
+}
+
+#[derive(Debug, Deserialize)]
+struct WitsmlTrajectorys {
+    #[serde(default)]
+    trajectory: Vec<WitsmlTrajectory>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct WitsmlTrajectory {
+    #[serde(rename = "@uid")]
+    uid: Option<String>,
+    #[serde(rename = "@uidWell")]
+    uid_well: Option<String>,
+    #[serde(rename = "@uidWellbore")]
+    uid_wellbore: Option<String>,
+    #[serde(default)]
+    name: Option<String>,
+    #[serde(default)]
+    trajectory_station: Vec<TrajectoryStation>,
+}
+
+#[derive(Debug, Deserialize)]
+struct TrajectoryStation {
+    md: Measure,
+    incl: Measure,
+    azi: Measure,
+}
+
+/// A WITSML measure element, e.g. `<md uom="ft">100.0</md>`.
+#[derive(Debug, Deserialize)]
+struct Measure {
+    #[serde(rename = "@uom")]
+    uom: Option<String>,
+    #[serde(rename = "$text")]
+    value: f64,
+}
+
+impl Measure {
+    /// Value in the canonical unit of `quantity`. A missing `uom` is taken
+    /// as already canonical.
+    fn canonical(&self, quantity: Quantity) -> Result<f64, WitsmlError> {
+        let unit = uom::lookup_for(self.uom.as_deref().unwrap_or(""), quantity)?;
+        Ok(unit.to_canonical(self.value))
+    }
+}
+
+/// Parses a WITSML `<trajectorys>` document into trajectories.
+///
+/// WITSML inclination is measured from vertical, while `SurveyPoint` carries
+/// HDD pitch (0 = horizontal, positive = descending), so `pitch = 90 - incl`.
+pub fn parse_trajectories(xml: &str) -> Result<Vec<Trajectory>, WitsmlError> {
+    let doc: WitsmlTrajectorys = from_str(xml)?;
+
+    doc.trajectory
+        .into_iter()
+        .map(|traj| {
+            let mut stations = traj
+                .trajectory_station
+                .iter()
+                .map(|stn| {
+                    Ok(SurveyPoint {
+                        measured_depth: stn.md.canonical(Quantity::Length)?,
+                        inclination: 90.0 - stn.incl.canonical(Quantity::Angle)?,
+                        azimuth: stn.azi.canonical(Quantity::Angle)?,
+                    })
+                })
+                .collect::<Result<Vec<_>, WitsmlError>>()?;
+            stations.sort_by(|a, b| a.measured_depth.total_cmp(&b.measured_depth));
+
+            Ok(Trajectory {
+                uid: traj.uid,
+                uid_well: traj.uid_well,
+                uid_wellbore: traj.uid_wellbore,
+                name: traj.name,
+                stations,
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LOG: &str = r#"
+        <log uid="log-1">
+            <logCurveInfo><mnemonic>TIME</mnemonic><unit>s</unit></logCurveInfo>
+            <logCurveInfo><mnemonic>DEPT</mnemonic><unit>ft</unit></logCurveInfo>
+            <logCurveInfo><mnemonic>TRQ</mnemonic><unit>ft.lbf</unit></logCurveInfo>
+            <logCurveInfo><mnemonic>WOB</mnemonic><unit>lbf</unit></logCurveInfo>
+            <logCurveInfo><mnemonic>GAMMA</mnemonic><unit>API</unit></logCurveInfo>
+            <logData>
+                <data>2024-05-01T10:00:00Z,100.0,1500,-999.25,42</data>
+                <data>2024-05-01T10:00:01Z,100.5,,2000,43</data>
+            </logData>
+        </log>"#;
+
+    #[test]
+    fn test_parse_rows_by_mnemonic() {
+        let rows = parse_witsml(LOG).unwrap();
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[0].timestamp, "2024-05-01T10:00:00Z");
+        assert_eq!(rows[0].depth, 100.0);
+        assert_eq!(rows[0].torque, Some(1500.0));
+        assert_eq!(rows[0].thrust, None);
+        assert_eq!(rows[1].torque, None);
+        assert_eq!(rows[1].thrust, Some(2000.0));
+        assert_eq!(rows[1].mud_flow, None);
+    }
+
+    #[test]
+    fn test_log_units_are_normalised() {
+        let xml = LOG
+            .replace("<unit>ft</unit>", "<unit>m</unit>")
+            .replace("<unit>ft.lbf</unit>", "<unit>kN.m</unit>");
+        let rows = parse_witsml(&xml).unwrap();
+        assert!((rows[0].depth - 328.083989).abs() < 1e-5);
+        assert!((rows[0].torque.unwrap() - 1_106_343.22).abs() < 0.01);
+
+        let xml = LOG.replace("<unit>lbf</unit>", "<unit>psi</unit>");
+        assert!(matches!(
+            parse_witsml(&xml),
+            Err(WitsmlError::IncompatibleUnit { .. })
+        ));
+    }
+
+    #[test]
+    fn test_custom_alias_table() {
+        let xml = LOG.replace(
+            "<mnemonic>GAMMA</mnemonic><unit>API</unit>",
+            "<mnemonic>SPIN</mnemonic><unit>rpm</unit>",
+        );
+        let mut aliases = MnemonicMap::default();
+        aliases.insert("SPIN", Channel::BitRpm);
+        let rows = parse_witsml_with(&xml, &aliases).unwrap();
+        assert_eq!(rows[0].bit_rpm, Some(42.0));
+
+        let rows = parse_witsml(&xml).unwrap();
+        assert_eq!(rows[0].bit_rpm, None);
+    }
+
+    #[test]
+    fn test_bad_cell_reports_row_and_column() {
+        let xml = LOG.replace(",2000,", ",abc,");
+        match parse_witsml(&xml) {
+            Err(WitsmlError::InvalidValue { row, column, value }) => {
+                assert_eq!(row, 1);
+                assert_eq!(column, "WOB");
+                assert_eq!(value, "abc");
+            }
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_logs_container_errors_are_reported() {
+        let container = format!("<logs>{}{}</logs>", LOG, LOG);
+        assert_eq!(parse_witsml(&container).unwrap().len(), 4);
+        assert!(parse_witsml("<logs/>").unwrap().is_empty());
+
+        // A broken log inside a container fails on that log, not as a bare <log>.
+        let broken = r#"<logs><log uid="log-2">
+            <logCurveInfo><mnemonic>DEPT</mnemonic><unit>ft</unit></logCurveInfo>
+        </log></logs>"#;
+        match parse_witsml(broken) {
+            Err(WitsmlError::XmlError(e)) => assert!(e.to_string().contains("logData"), "{e}"),
+            other => panic!("unexpected result: {:?}", other),
+        }
+        assert!(matches!(
+            parse_witsml("<trajectorys/>"),
+            Err(WitsmlError::UnexpectedRoot(root)) if root == "trajectorys"
+        ));
+    }
+
+    #[test]
+    fn test_parse_trajectory_units() {
+        let xml = r#"
+            <trajectorys xmlns="http://www.witsml.org/schemas/1series" version="1.4.1.1">
+              <trajectory uidWell="well-1" uidWellbore="bore-1" uid="traj-1">
+                <trajectoryStation uid="stn-2">
+                  <md uom="m">30.48</md>
+                  <incl uom="rad">1.5707963267948966</incl>
+                  <azi uom="dega">45.0</azi>
+                </trajectoryStation>
+                <trajectoryStation uid="stn-1">
+                  <md uom="ft">0.0</md>
+                  <incl uom="dega">80.0</incl>
+                  <azi uom="dega">45.0</azi>
+                </trajectoryStation>
+              </trajectory>
+            </trajectorys>"#;
+        let trajs = parse_trajectories(xml).unwrap();
+        assert_eq!(trajs.len(), 1);
+        assert_eq!(trajs[0].uid_well.as_deref(), Some("well-1"));
+        assert_eq!(trajs[0].uid_wellbore.as_deref(), Some("bore-1"));
+
+        let stations = &trajs[0].stations;
+        assert_eq!(stations[0].measured_depth, 0.0);
+        assert!((stations[0].inclination - 10.0).abs() < 1e-9);
+        assert!((stations[1].measured_depth - 100.0).abs() < 1e-9);
+        assert!(stations[1].inclination.abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_trajectory_unknown_unit() {
+        let xml = r#"
+            <trajectorys>
+              <trajectory uid="traj-1">
+                <trajectoryStation>
+                  <md uom="furlong">1</md><incl>90</incl><azi>0</azi>
+                </trajectoryStation>
+              </trajectory>
+            </trajectorys>"#;
+        assert!(matches!(
+            parse_trajectories(xml),
+            Err(WitsmlError::UnknownUnit(u)) if u == "furlong"
+        ));
+    }
 }
@@ -0,0 +1,284 @@
+use crate::mnemonics::MnemonicMap;
+use crate::witsml_parser::{
+    parse_row, resolve_columns, Column, LogCurveInfo, TelemetryData, WitsmlError,
+};
+use quick_xml::events::Event;
+use quick_xml::Reader;
+use std::io::{BufRead, BufReader, Read};
+use tokio::io::AsyncBufRead;
+
+/// Element whose text is currently being collected.
+#[derive(Debug, Clone, Copy)]
+enum Field {
+    LogNullValue,
+    Mnemonic,
+    Unit,
+    CurveNullValue,
+    Data,
+}
+
+/// Event-driven state shared by the blocking and async readers.
+///
+/// Only the curve header and the current `<data>` row are held in memory.
+struct LogState {
+    mnemonics: MnemonicMap,
+    log_null_value: Option<String>,
+    curves: Vec<LogCurveInfo>,
+    current_curve: Option<LogCurveInfo>,
+    columns: Option<Vec<Column>>,
+    in_log_data: bool,
+    field: Option<Field>,
+    text: String,
+    row: usize,
+}
+
+impl LogState {
+    fn new(mnemonics: MnemonicMap) -> Self {
+        Self {
+            mnemonics,
+            log_null_value: None,
+            curves: Vec::new(),
+            current_curve: None,
+            columns: None,
+            in_log_data: false,
+            field: None,
+            text: String::new(),
+            row: 0,
+        }
+    }
+
+    fn handle(&mut self, event: Event) -> Result<Option<TelemetryData>, WitsmlError> {
+        match event {
+            Event::Start(e) => {
+                let in_curve = self.current_curve.is_some();
+                self.field = match e.local_name().as_ref() {
+                    b"log" => {
+                        self.log_null_value = None;
+                        self.curves.clear();
+                        self.columns = None;
+                        self.row = 0;
+                        None
+                    }
+                    b"logCurveInfo" => {
+                        self.current_curve = Some(LogCurveInfo::default());
+                        None
+                    }
+                    b"logData" => {
+                        let curves = std::mem::take(&mut self.curves);
+                        self.columns = Some(resolve_columns(
+                            curves,
+                            self.log_null_value.as_deref(),
+                            &self.mnemonics,
+                        )?);
+                        self.in_log_data = true;
+                        None
+                    }
+                    b"mnemonic" if in_curve => Some(Field::Mnemonic),
+                    b"unit" if in_curve => Some(Field::Unit),
+                    b"nullValue" if in_curve => Some(Field::CurveNullValue),
+                    b"nullValue" if !self.in_log_data => Some(Field::LogNullValue),
+                    b"data" if self.in_log_data => Some(Field::Data),
+                    _ => None,
+                };
+                self.text.clear();
+            }
+            Event::Text(t) if self.field.is_some() => self.text.push_str(&t.unescape()?),
+            Event::CData(t) if self.field.is_some() => {
+                let bytes = t.into_inner();
+                self.text.push_str(
+                    std::str::from_utf8(&bytes).map_err(|_| WitsmlError::ConversionError)?,
+                );
+            }
+            Event::End(e) => {
+                if let Some(field) = self.field.take() {
+                    let text = self.text.trim().to_string();
+                    match field {
+                        Field::LogNullValue => self.log_null_value = Some(text),
+                        Field::Mnemonic => self.curve().mnemonic = text,
+                        Field::Unit => self.curve().unit = text,
+                        Field::CurveNullValue => self.curve().null_value = Some(text),
+                        Field::Data => {
+                            let row = self.row;
+                            self.row += 1;
+                            let columns = self.columns.as_deref().unwrap_or_default();
+                            return parse_row(&text, row, columns).map(Some);
+                        }
+                    }
+                    return Ok(None);
+                }
+                match e.local_name().as_ref() {
+                    b"logCurveInfo" => {
+                        if let Some(curve) = self.current_curve.take() {
+                            self.curves.push(curve);
+                        }
+                    }
+                    b"logData" => self.in_log_data = false,
+                    _ => {}
+                }
+            }
+            _ => {}
+        }
+        Ok(None)
+    }
+
+    fn curve(&mut self) -> &mut LogCurveInfo {
+        self.current_curve.get_or_insert_with(LogCurveInfo::default)
+    }
+}
+
+/// Pull-parser over a WITSML `<log>` (or `<logs>`) document.
+///
+/// Rows are parsed one `<data>` element at a time, so memory use does not
+/// grow with the size of the file. A bad row is reported as an `Err` item
+/// and reading continues; XML errors end the iteration.
+pub struct WitsmlLogReader<R> {
+    reader: Reader<R>,
+    buf: Vec<u8>,
+    state: LogState,
+    done: bool,
+}
+
+impl<R> WitsmlLogReader<R> {
+    /// Creates a reader using the built-in mnemonic aliases.
+    pub fn new(reader: R) -> Self {
+        Self::with_mnemonics(reader, MnemonicMap::default())
+    }
+
+    pub fn with_mnemonics(reader: R, mnemonics: MnemonicMap) -> Self {
+        let mut reader = Reader::from_reader(reader);
+        reader.trim_text(true);
+        Self {
+            reader,
+            buf: Vec::new(),
+            state: LogState::new(mnemonics),
+            done: false,
+        }
+    }
+
+    /// Row-level errors leave the reader usable; anything else ends it.
+    fn fail(&mut self, error: WitsmlError) -> WitsmlError {
+        if !matches!(
+            error,
+            WitsmlError::InvalidValue { .. }
+                | WitsmlError::ColumnCount { .. }
+                | WitsmlError::NullDepth { .. }
+        ) {
+            self.done = true;
+        }
+        error
+    }
+}
+
+impl<R: Read> WitsmlLogReader<BufReader<R>> {
+    /// Wraps an unbuffered reader (e.g. a `File`) in a `BufReader`.
+    pub fn from_read(reader: R) -> Self {
+        Self::new(BufReader::new(reader))
+    }
+}
+
+impl<R: BufRead> Iterator for WitsmlLogReader<R> {
+    type Item = Result<TelemetryData, WitsmlError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while !self.done {
+            self.buf.clear();
+            let event = match self.reader.read_event_into(&mut self.buf) {
+                Ok(Event::Eof) => break,
+                Ok(event) => event,
+                Err(e) => {
+                    self.done = true;
+                    return Some(Err(e.into()));
+                }
+            };
+            match self.state.handle(event) {
+                Ok(Some(data)) => return Some(Ok(data)),
+                Ok(None) => {}
+                Err(e) => return Some(Err(self.fail(e))),
+            }
+        }
+        self.done = true;
+        None
+    }
+}
+
+impl<R: AsyncBufRead + Unpin> WitsmlLogReader<R> {
+    /// Async counterpart of `Iterator::next`, for tokio readers such as a
+    /// `BufReader<File>` or a network stream.
+    pub async fn next_row(&mut self) -> Option<Result<TelemetryData, WitsmlError>> {
+        while !self.done {
+            self.buf.clear();
+            let event = match self.reader.read_event_into_async(&mut self.buf).await {
+                Ok(Event::Eof) => break,
+                Ok(event) => event,
+                Err(e) => {
+                    self.done = true;
+                    return Some(Err(e.into()));
+                }
+            };
+            match self.state.handle(event) {
+                Ok(Some(data)) => return Some(Ok(data)),
+                Ok(None) => {}
+                Err(e) => return Some(Err(self.fail(e))),
+            }
+        }
+        self.done = true;
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::witsml_parser::parse_witsml;
+
+    const LOG: &str = r#"
+        <logs xmlns="http://www.witsml.org/schemas/1series" version="1.4.1.1">
+          <log uid="log-1">
+            <nullValue>-999.25</nullValue>
+            <logCurveInfo uid="c1"><mnemonic>TIME</mnemonic><unit>s</unit></logCurveInfo>
+            <logCurveInfo uid="c2"><mnemonic>DEPT</mnemonic><unit>m</unit></logCurveInfo>
+            <logCurveInfo uid="c3"><mnemonic>TRQ</mnemonic><unit>ft.lbf</unit></logCurveInfo>
+            <logData>
+              <data>2024-05-01T10:00:00Z,30.48,1500</data>
+              <data>2024-05-01T10:00:01Z,30.60,x</data>
+              <data>2024-05-01T10:00:02Z,30.70,-999.25</data>
+            </logData>
+          </log>
+        </logs>"#;
+
+    #[test]
+    fn test_stream_rows_and_continue_after_bad_row() {
+        let rows: Vec<_> = WitsmlLogReader::new(LOG.as_bytes()).collect();
+        assert_eq!(rows.len(), 3);
+        let first = rows[0].as_ref().unwrap();
+        assert!((first.depth - 100.0).abs() < 1e-9);
+        assert_eq!(first.torque, Some(1500.0));
+        assert!(matches!(
+            rows[1],
+            Err(WitsmlError::InvalidValue { row: 1, .. })
+        ));
+        assert_eq!(rows[2].as_ref().unwrap().torque, None);
+    }
+
+    #[test]
+    fn test_stream_matches_dom_parser() {
+        let xml = LOG.replace(",x<", ",1600<");
+        let streamed: Vec<_> = WitsmlLogReader::new(xml.as_bytes())
+            .collect::<Result<_, _>>()
+            .unwrap();
+        let log_start = xml.find("<log ").unwrap();
+        let log_end = xml.find("</logs>").unwrap();
+        assert_eq!(streamed, parse_witsml(&xml[log_start..log_end]).unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_async_reader() {
+        let mut reader = WitsmlLogReader::new(LOG.as_bytes());
+        let mut rows = Vec::new();
+        while let Some(row) = reader.next_row().await {
+            rows.push(row);
+        }
+        assert_eq!(rows.len(), 3);
+        assert!(rows[0].is_ok() && rows[1].is_err() && rows[2].is_ok());
+    }
+}
@@ -0,0 +1,333 @@
+use crate::witsml_parser::{TelemetryData, WitsmlError};
+use crate::{Coordinate3D, SurveyPoint};
+use serde::Serialize;
+
+const WITSML_NAMESPACE: &str = "http://www.witsml.org/schemas/1series";
+const WITSML_VERSION: &str = "1.4.1.1";
+const XML_DECLARATION: &str = r#"<?xml version="1.0" encoding="UTF-8"?>"#;
+
+/// Identifiers written on exported WITSML objects.
+#[derive(Debug, Clone, PartialEq)]
+pub struct WitsmlIds {
+    pub uid_well: String,
+    pub uid_wellbore: String,
+    pub uid: String,
+    pub name_well: String,
+    pub name_wellbore: String,
+    pub name: String,
+}
+
+#[derive(Serialize)]
+struct Measure {
+    #[serde(rename = "@uom")]
+    uom: &'static str,
+    #[serde(rename = "$text")]
+    value: f64,
+}
+
+impl Measure {
+    fn ft(value: f64) -> Self {
+        Self { uom: "ft", value }
+    }
+
+    fn dega(value: f64) -> Self {
+        Self { uom: "dega", value }
+    }
+}
+
+#[derive(Serialize)]
+#[serde(rename = "trajectorys")]
+struct TrajectorysOut {
+    #[serde(rename = "@xmlns")]
+    xmlns: &'static str,
+    #[serde(rename = "@version")]
+    version: &'static str,
+    trajectory: TrajectoryOut,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct TrajectoryOut {
+    #[serde(rename = "@uidWell")]
+    uid_well: String,
+    #[serde(rename = "@uidWellbore")]
+    uid_wellbore: String,
+    #[serde(rename = "@uid")]
+    uid: String,
+    name_well: String,
+    name_wellbore: String,
+    name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    md_mn: Option<Measure>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    md_mx: Option<Measure>,
+    trajectory_station: Vec<StationOut>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct StationOut {
+    #[serde(rename = "@uid")]
+    uid: String,
+    type_traj_station: &'static str,
+    md: Measure,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    tvd: Option<Measure>,
+    incl: Measure,
+    azi: Measure,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    disp_ns: Option<Measure>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    disp_ew: Option<Measure>,
+}
+
+/// Writes survey stations as a WITSML 1.4.1 `<trajectorys>` document.
+///
+/// `SurveyPoint` pitch is written as WITSML inclination (`incl = 90 - pitch`).
+/// When the computed `path` is given (one coordinate per station, as returned
+/// by `calculate_path`), each station also carries `tvd`, `dispNs` and
+/// `dispEw`.
+pub fn write_trajectory(
+    ids: &WitsmlIds,
+    surveys: &[SurveyPoint],
+    path: Option<&[Coordinate3D]>,
+) -> Result<String, WitsmlError> {
+    let stations = surveys
+        .iter()
+        .enumerate()
+        .map(|(i, s)| {
+            let coord = path.and_then(|p| p.get(i));
+            StationOut {
+                uid: format!("stn-{}", i + 1),
+                type_traj_station: "unknown",
+                md: Measure::ft(s.measured_depth),
+                tvd: coord.map(|c| Measure::ft(c.tvd)),
+                incl: Measure::dega(90.0 - s.inclination),
+                azi: Measure::dega(s.azimuth),
+                disp_ns: coord.map(|c| Measure::ft(c.north)),
+                disp_ew: coord.map(|c| Measure::ft(c.east)),
+            }
+        })
+        .collect();
+
+    let doc = TrajectorysOut {
+        xmlns: WITSML_NAMESPACE,
+        version: WITSML_VERSION,
+        trajectory: TrajectoryOut {
+            uid_well: ids.uid_well.clone(),
+            uid_wellbore: ids.uid_wellbore.clone(),
+            uid: ids.uid.clone(),
+            name_well: ids.name_well.clone(),
+            name_wellbore: ids.name_wellbore.clone(),
+            name: ids.name.clone(),
+            md_mn: surveys.first().map(|s| Measure::ft(s.measured_depth)),
+            md_mx: surveys.last().map(|s| Measure::ft(s.measured_depth)),
+            trajectory_station: stations,
+        },
+    };
+    to_xml(&doc)
+}
+
+#[derive(Serialize)]
+#[serde(rename = "logs")]
+struct LogsOut {
+    #[serde(rename = "@xmlns")]
+    xmlns: &'static str,
+    #[serde(rename = "@version")]
+    version: &'static str,
+    log: LogOut,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct LogOut {
+    #[serde(rename = "@uidWell")]
+    uid_well: String,
+    #[serde(rename = "@uidWellbore")]
+    uid_wellbore: String,
+    #[serde(rename = "@uid")]
+    uid: String,
+    name_well: String,
+    name_wellbore: String,
+    name: String,
+    index_type: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    start_index: Option<Measure>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    end_index: Option<Measure>,
+    direction: &'static str,
+    index_curve: &'static str,
+    log_curve_info: Vec<CurveOut>,
+    log_data: LogDataOut,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct CurveOut {
+    #[serde(rename = "@uid")]
+    uid: &'static str,
+    mnemonic: &'static str,
+    unit: &'static str,
+    type_log_data: &'static str,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct LogDataOut {
+    mnemonic_list: String,
+    unit_list: String,
+    data: Vec<String>,
+}
+
+/// Curves written to a log, index curve first: (mnemonic, unit, data type).
+const LOG_CURVES: [(&str, &str, &str); 6] = [
+    ("DEPT", "ft", "double"),
+    ("TIME", "", "date time"),
+    ("TQA", "ft.lbf", "double"),
+    ("THRUST", "lbf", "double"),
+    ("MFIA", "galUS/min", "double"),
+    ("RPMA", "rpm", "double"),
+];
+
+/// Writes telemetry as a depth-indexed WITSML 1.4.1 `<logs>` document.
+///
+/// Missing readings are written as empty cells, which WITSML treats as null.
+pub fn write_log(ids: &WitsmlIds, rows: &[TelemetryData]) -> Result<String, WitsmlError> {
+    let has_time = rows.iter().any(|r| !r.timestamp.is_empty());
+    let curves: Vec<_> = LOG_CURVES
+        .iter()
+        .filter(|(mnemonic, _, _)| has_time || *mnemonic != "TIME")
+        .collect();
+
+    let cell = |v: Option<f64>| v.map(|v| v.to_string()).unwrap_or_default();
+    let data = rows
+        .iter()
+        .map(|r| {
+            let mut cells = vec![r.depth.to_string()];
+            if has_time {
+                cells.push(r.timestamp.clone());
+            }
+            cells.extend([
+                cell(r.torque),
+                cell(r.thrust),
+                cell(r.mud_flow),
+                cell(r.bit_rpm),
+            ]);
+            cells.join(",")
+        })
+        .collect();
+
+    let doc = LogsOut {
+        xmlns: WITSML_NAMESPACE,
+        version: WITSML_VERSION,
+        log: LogOut {
+            uid_well: ids.uid_well.clone(),
+            uid_wellbore: ids.uid_wellbore.clone(),
+            uid: ids.uid.clone(),
+            name_well: ids.name_well.clone(),
+            name_wellbore: ids.name_wellbore.clone(),
+            name: ids.name.clone(),
+            index_type: "measured depth",
+            start_index: rows.first().map(|r| Measure::ft(r.depth)),
+            end_index: rows.last().map(|r| Measure::ft(r.depth)),
+            direction: "increasing",
+            index_curve: "DEPT",
+            log_curve_info: curves
+                .iter()
+                .map(|(mnemonic, unit, type_log_data)| CurveOut {
+                    uid: mnemonic,
+                    mnemonic,
+                    unit,
+                    type_log_data,
+                })
+                .collect(),
+            log_data: LogDataOut {
+                mnemonic_list: curves.iter().map(|c| c.0).collect::<Vec<_>>().join(","),
+                unit_list: curves.iter().map(|c| c.1).collect::<Vec<_>>().join(","),
+                data,
+            },
+        },
+    };
+    to_xml(&doc)
+}
+
+fn to_xml<T: Serialize>(doc: &T) -> Result<String, WitsmlError> {
+    let body = quick_xml::se::to_string(doc)?;
+    Ok(format!("{}\n{}", XML_DECLARATION, body))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::calculate_path;
+    use crate::witsml_parser::{parse_trajectories, parse_witsml};
+
+    fn ids() -> WitsmlIds {
+        WitsmlIds {
+            uid_well: "well-1".into(),
+            uid_wellbore: "bore-1".into(),
+            uid: "asbuilt-1".into(),
+            name_well: "Main St Crossing".into(),
+            name_wellbore: "Bore 1".into(),
+            name: "As-built".into(),
+        }
+    }
+
+    #[test]
+    fn test_trajectory_round_trip() {
+        let surveys = vec![
+            SurveyPoint {
+                measured_depth: 0.0,
+                inclination: 12.0,
+                azimuth: 90.0,
+            },
+            SurveyPoint {
+                measured_depth: 15.0,
+                inclination: 8.5,
+                azimuth: 90.5,
+            },
+            SurveyPoint {
+                measured_depth: 30.0,
+                inclination: 0.0,
+                azimuth: 91.0,
+            },
+        ];
+        let path = calculate_path(&surveys).unwrap();
+        let xml = write_trajectory(&ids(), &surveys, Some(&path)).unwrap();
+        assert!(xml.contains(r#"uidWellbore="bore-1""#));
+        assert!(xml.contains("<dispEw uom=\"ft\">"));
+
+        let parsed = parse_trajectories(&xml).unwrap();
+        assert_eq!(parsed[0].uid.as_deref(), Some("asbuilt-1"));
+        for (a, b) in parsed[0].stations.iter().zip(&surveys) {
+            assert!((a.measured_depth - b.measured_depth).abs() < 1e-9);
+            assert!((a.inclination - b.inclination).abs() < 1e-9);
+            assert!((a.azimuth - b.azimuth).abs() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn test_log_round_trip() {
+        let rows = vec![
+            TelemetryData {
+                timestamp: "2024-05-01T10:00:00Z".into(),
+                depth: 100.0,
+                torque: Some(1500.0),
+                thrust: None,
+                mud_flow: Some(60.5),
+                bit_rpm: Some(80.0),
+            },
+            TelemetryData {
+                timestamp: "2024-05-01T10:00:01Z".into(),
+                depth: 100.5,
+                torque: Some(1510.0),
+                thrust: Some(12000.0),
+                mud_flow: None,
+                bit_rpm: Some(81.0),
+            },
+        ];
+        let xml = write_log(&ids(), &rows).unwrap();
+        assert!(xml.contains("<indexCurve>DEPT</indexCurve>"));
+        assert_eq!(parse_witsml(&xml).unwrap(), rows);
+    }
+}